@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    config::UserConfig,
+    dirs::FygDirs,
+    engine::{self, Variant},
+    platform::Platform,
+    releases::{RELEASE_METADATA_NAME, SHA512_SUMS_NAME},
+    version::Version,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// What a file in the cache is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    /// An engine archive, for any platform.
+    Engine,
+    /// An export templates archive.
+    Templates,
+    /// A release's SHA512-SUMS.txt.
+    Sums,
+    /// A release's GitHub metadata, from an imported bundle.
+    Release,
+    /// Anything fyg didn't download, or that isn't in a version's directory.
+    Unknown,
+}
+
+/// A file in the download cache, as printed by `cache --format json`.
+#[derive(Debug, Serialize)]
+pub struct CacheEntry {
+    /// The version the file was downloaded for, without a stable suffix. e.g. "4.3" or "4.3-rc2"
+    pub version: Option<String>,
+    /// The release tag the file was downloaded for, from its directory's name. e.g. "4.3-stable"
+    /// Directories that aren't named like a version have no tag, and their files are unknown.
+    pub tag: Option<String>,
+    pub kind: CacheKind,
+    /// Which build an engine or templates archive is for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<Variant>,
+    /// The file's name.
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    /// When the file was last downloaded or installed from. Kept as its modification time.
+    pub last_used: DateTime<Utc>,
+}
+
+impl CacheEntry {
+    fn new(path: PathBuf, dir_name: Option<&str>) -> Result<CacheEntry> {
+        let metadata = path.metadata()?;
+        let name = path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let tag = dir_name.filter(|dir_name| Version::parse(dir_name).is_some());
+        let (kind, variant) = match tag {
+            Some(tag) => classify(&name, tag),
+            None => (CacheKind::Unknown, None),
+        };
+        Ok(CacheEntry {
+            version: tag.map(|tag| tag.strip_suffix("-stable").unwrap_or(tag).to_string()),
+            tag: tag.map(str::to_string),
+            kind,
+            variant,
+            name,
+            path,
+            size: metadata.len(),
+            last_used: metadata.modified()?.into(),
+        })
+    }
+
+    /// Whether this entry was downloaded for the given version, in any of its forms. e.g. "4.3",
+    /// "4.3.0" and "4.3-stable" are all the same version.
+    pub fn matches_version(&self, version: &str) -> bool {
+        match (self.tag.as_deref().and_then(Version::parse), Version::parse(version)) {
+            (Some(tag_version), Some(version)) => tag_version == version,
+            _ => false,
+        }
+    }
+}
+
+/// Work out what a file in a release's cache directory is from its name.
+fn classify(name: &str, full_version: &str) -> (CacheKind, Option<Variant>) {
+    if name == SHA512_SUMS_NAME {
+        return (CacheKind::Sums, None);
+    }
+    if name == RELEASE_METADATA_NAME {
+        return (CacheKind::Release, None);
+    }
+    for variant in Variant::ALL {
+        if name == engine::get_templates_name(full_version, variant) {
+            return (CacheKind::Templates, Some(variant));
+        }
+        let is_engine = Platform::ALL.into_iter()
+            .any(|platform| name == engine::get_archive_name_for(platform, full_version, variant));
+        if is_engine {
+            return (CacheKind::Engine, Some(variant));
+        }
+    }
+    (CacheKind::Unknown, None)
+}
+
+/// Every file in the download cache, sorted by path.
+pub fn entries() -> Result<Vec<CacheEntry>> {
+    let engines_cache_dir = FygDirs::get().engines_cache();
+    let mut entries = Vec::new();
+    if !engines_cache_dir.is_dir() {
+        return Ok(entries);
+    }
+
+    for version_entry in fs::read_dir(engines_cache_dir)? {
+        let version_entry = version_entry?;
+        let version_path = version_entry.path();
+        if !version_path.is_dir() {
+            // Downloads always go in a version's directory, so this doesn't belong to one.
+            entries.push(CacheEntry::new(version_path, None)?);
+            continue;
+        }
+        let dir_name = version_entry.file_name().to_string_lossy().into_owned();
+        for entry in fs::read_dir(&version_path)? {
+            let path = entry?.path();
+            if path.is_file() {
+                entries.push(CacheEntry::new(path, Some(&dir_name))?);
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Mark a cached download as just used, so it's evicted last.
+pub fn touch(path: &Path) -> Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()))
+        .with_context(|| format!("Could not update {}.", path.display()))
+}
+
+/// Remove a cached file, along with its version's directory once nothing is left in it.
+pub fn remove(entry: &CacheEntry) -> Result<()> {
+    fs::remove_file(&entry.path)
+        .with_context(|| format!("Could not remove {}.", entry.path.display()))?;
+    if let Some(version_dir) = entry.path.parent() {
+        let is_cache_dir = version_dir == FygDirs::get().engines_cache();
+        if !is_cache_dir && fs::read_dir(version_dir)?.next().is_none() {
+            fs::remove_dir(version_dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove downloads that are older than the user's `cache_max_age_days`, then the least recently
+/// used ones until the cache fits in `cache_max_size`. Only engine and export templates archives
+/// are removed, since installs rely on the sums and imported bundles on the release metadata.
+/// `keep` is never removed. Returns the entries that were (or with `dry_run`, would be) removed.
+pub fn collect_garbage(keep: Option<&Path>, dry_run: bool) -> Result<Vec<CacheEntry>> {
+    let user_config = UserConfig::get();
+    let max_age = user_config.cache_max_age_days
+        .map(|days| Duration::from_secs(days * SECONDS_PER_DAY));
+    let max_size = user_config.cache_max_size();
+    if max_age.is_none() && max_size.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = entries()?;
+    // Least recently used first.
+    entries.sort_by_key(|entry| entry.last_used);
+    let mut total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+
+    let now = Utc::now();
+    let mut evicted = Vec::new();
+    for entry in entries {
+        if keep == Some(entry.path.as_path()) || !matches!(entry.kind, CacheKind::Engine | CacheKind::Templates) {
+            continue;
+        }
+        let age = now.signed_duration_since(entry.last_used)
+            .to_std()
+            .unwrap_or_default();
+        let too_old = max_age.is_some_and(|max_age| age > max_age);
+        let too_big = max_size.is_some_and(|max_size| total_size > max_size);
+        if !too_old && !too_big {
+            continue;
+        }
+
+        if !dry_run {
+            remove(&entry)?;
+        }
+        total_size -= entry.size;
+        evicted.push(entry);
+    }
+
+    Ok(evicted)
+}
@@ -0,0 +1,35 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha512};
+
+/// Compute the hex encoded SHA512 digest of a file.
+pub fn sha512_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)
+        .with_context(|| format!("Could not open {}.", path.display()))?;
+    let mut hasher = Sha512::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Parse a sums file in the format written by `sha512sum`, mapping file names to digests.
+pub fn parse_sums(sums: &str) -> HashMap<String, String> {
+    sums.lines()
+        .filter_map(|line| {
+            let (digest, name) = line.trim().split_once(char::is_whitespace)?;
+            // sha512sum marks binary mode with a leading '*'.
+            let name = name.trim_start().trim_start_matches('*');
+            Some((name.to_string(), digest.to_ascii_lowercase()))
+        })
+        .collect()
+}
+
+/// Compute the hex encoded SHA512 digest of some bytes.
+pub fn sha512_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha512::digest(bytes))
+}
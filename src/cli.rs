@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 use std::sync::LazyLock;
 
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, ArgGroup, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCandidates;
+
+use crate::{completions, output::Format};
 
 static VERSION: LazyLock<String> = LazyLock::new(||
     format!("{} ({})", clap::crate_version!(), env!("VERGEN_GIT_SHA"))
@@ -12,6 +15,32 @@ static VERSION: LazyLock<String> = LazyLock::new(||
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<CliCommand>,
+
+    /// How to print results and errors.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub format: Format,
+
+    /// Show more of what fyg is doing. Repeat for more detail, e.g. -vv.
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Only show errors, without warnings or download progress.
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Also write a detailed log to this file, adding to what's already there.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+}
+
+impl Cli {
+    /// How to print results and errors, taking flags like `outdated --json` into account.
+    pub fn format(&self) -> Format {
+        match self.command {
+            Some(CliCommand::Outdated { json: true, .. }) => Format::Json,
+            _ => self.format,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -21,38 +50,103 @@ pub enum CliCommand {
         /// Show all Godot engine versions available on GitHub.
         #[arg(short, long)]
         available: bool,
+
+        /// Only show versions starting with this. e.g. "4.2" shows 4.2, 4.2.1 and 4.2-rc1, but not 4.20.
+        prefix: Option<String>,
+
+        /// Only show versions whose full tag matches this regular expression. e.g. "^4\.\d-stable$"
+        #[arg(long)]
+        regex: Option<String>,
+
+        /// Show every prerelease. By default only prereleases newer than the latest stable release are shown.
+        #[arg(long, requires = "available", conflicts_with = "stable_only")]
+        prereleases: bool,
+
+        /// Hide every prerelease.
+        #[arg(long, requires = "available")]
+        stable_only: bool,
+
+        /// List Mono versions with C# support instead.
+        #[arg(long)]
+        mono: bool,
     },
 
     /// Install the given Godot engine version.
+    #[command(group(ArgGroup::new("source").args(["version", "from_file"]).multiple(true)))]
     Install {
-        /// Which version to install. e.g. "3.5.1"
-        version: String,
+        /// Which version to install. e.g. "3.5.1". If none specified, install what the fyg.toml in the current directory asks for.
+        #[arg(add = ArgValueCandidates::new(completions::available_versions))]
+        version: Option<String>,
+
+        /// Install the Mono version with C# support.
+        #[arg(long, requires = "source")]
+        mono: bool,
 
-        // Install the Mono version with C# support.
-        // #[arg(long)]
-        // mono: bool,
+        /// Also install the export templates.
+        #[arg(long, requires = "version", conflicts_with = "from_file")]
+        templates: bool,
 
         /// Re-install if already installed.
         #[arg(short, long)]
         force: bool,
+
+        /// Install from a downloaded engine or export templates archive instead of downloading one.
+        #[arg(long, value_name = "ARCHIVE")]
+        from_file: Option<PathBuf>,
+
+        /// SHA512 digest to check the archive given with --from-file against.
+        #[arg(long, requires = "from_file")]
+        sha512: Option<String>,
+
+        /// SHA512-SUMS.txt to check the archive given with --from-file against. By default, one next to the archive is used.
+        #[arg(long, requires = "from_file", conflicts_with = "sha512")]
+        sums: Option<PathBuf>,
     },
 
     /// Uninstall the given Godot engine version.
     Uninstall {
         /// Which version to uninstall. e.g. "3.5.1"
+        #[arg(add = ArgValueCandidates::new(completions::installed_versions))]
+        version: String,
+
+        /// Uninstall the Mono version with C# support instead.
+        #[arg(long)]
+        mono: bool,
+
+        /// Only show what would be removed.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Uninstall without asking for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show details about a Godot engine version's release and install.
+    Info {
+        /// Which version to show. e.g. "4.3"
         version: String,
     },
 
     /// Launch the given Godot engine version.
     Launch {
-        /// Which version to launch. e.g. "3.5.1"
-        version: String,
+        /// Which version to launch. e.g. "3.5.1". If none specified, launch the default_version from the user config.
+        #[arg(add = ArgValueCandidates::new(completions::installed_versions))]
+        version: Option<String>,
+
+        /// Launch the Mono version with C# support instead.
+        #[arg(long)]
+        mono: bool,
     },
 
     /// Edit a Godot project with its associated Godot engine.
     Edit {
         /// Path to a project directory to edit that contains a fyg.toml file. If none specified, try the current directory.
         project_dir: Option<PathBuf>,
+
+        /// Install the project's engine and export templates first if they're missing.
+        #[arg(long)]
+        install: bool,
     },
 
     /// Show or remove files from fyg's cache. Shows downloaded engine versions by default.
@@ -60,6 +154,150 @@ pub enum CliCommand {
         #[command(subcommand)]
         cache_command: Option<CacheCommand>,
     },
+
+    /// Pack cached engines into a single file to install them on a machine without internet access.
+    Bundle {
+        #[command(subcommand)]
+        bundle_command: BundleCommand,
+    },
+
+    /// Create a fyg.toml for the Godot project in or below the current directory.
+    Init {
+        /// Which version to use instead of the one detected from project.godot. e.g. "4.3"
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Overwrite an existing config file.
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Check a project's config or manage the user's global config.
+    Config {
+        #[command(subcommand)]
+        config_command: ConfigCommand,
+    },
+
+    /// Browse and manage engines, releases, the cache and projects in a full-screen interface.
+    Tui,
+
+    /// Print a shell script that sets up tab completion, including for versions.
+    Completions {
+        /// Which shell to print the script for.
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Check fyg's directories, GitHub access, installed engines and the current project for problems.
+    Doctor {
+        /// Path to a project directory that contains a fyg.toml file. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+    },
+
+    /// Show where fyg keeps its config, engines and downloads, and what decided each.
+    Dirs,
+
+    /// Show which config file decides a project's engine version.
+    WhichConfig {
+        /// Path to a project directory. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+    },
+
+    /// List or manage the projects fyg has opened. Lists known projects by default.
+    Projects {
+        #[command(subcommand)]
+        projects_command: Option<ProjectsCommand>,
+    },
+
+    /// Remove installed engines that no known project or the default version uses.
+    Prune {
+        /// Only show which engines would be removed.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also remove the engines' downloads from the cache.
+        #[arg(long)]
+        cache: bool,
+
+        /// Also remove the newest installed patch of each minor series.
+        #[arg(long)]
+        include_newest: bool,
+
+        /// Remove without asking for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show newer releases of the installed engines and the project's pinned version.
+    Outdated {
+        /// Path to a project directory that contains a fyg.toml file. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+
+        /// Exit with status 2 if the project's version has a newer patch release, e.g. for CI.
+        #[arg(long)]
+        check: bool,
+
+        /// Print JSON. Short for --format json.
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+
+    /// Upgrade a project to the newest patch release of its engine, or to the given version.
+    Upgrade {
+        /// Path to a project directory that contains a fyg.toml file. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+
+        /// Version to upgrade to instead of the newest patch release. e.g. "4.3"
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Uninstall the old engine without asking, if no other project uses it.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Install the engine and export templates a project's fyg.toml asks for.
+    Sync {
+        /// Path to a project directory that contains a fyg.toml file. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+
+        /// Re-install if already installed.
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Install a project's engine into its .fyg directory, so the project doesn't depend on shared engines.
+    Vendor {
+        /// Path to a project directory that contains a fyg.toml file. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+
+        /// Also install the export templates.
+        #[arg(long)]
+        templates: bool,
+
+        /// Re-install if already installed.
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Pin a project's engine to an exact release and archive digests in a fyg.lock file.
+    Lock {
+        /// Path to a project directory that contains a fyg.toml file. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+
+        /// Re-resolve the project's version even if fyg.lock is up to date.
+        #[arg(short, long)]
+        update: bool,
+    },
+}
+
+/// A shell fyg can print a completion script for.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
 }
 
 #[derive(Debug, Subcommand)]
@@ -74,6 +312,97 @@ pub enum CacheCommand {
         all: bool,
 
         /// Which downloaded engine versions to remove. e.g. "3.5.1 4.0.3"
+        #[arg(add = ArgValueCandidates::new(completions::cached_versions))]
         versions: Vec<String>,
+
+        /// Only show what would be removed.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Remove without asking for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Remove downloads that are older or don't fit in the cache_max_age_days and cache_max_size settings.
+    Gc {
+        /// Only show which downloads would be removed.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BundleCommand {
+    /// Pack engine versions from the cache into a bundle, downloading any that aren't cached.
+    Create {
+        /// Where to write the bundle. e.g. "engines.tar"
+        path: PathBuf,
+
+        /// Which engine versions to pack. e.g. "4.3 3.6"
+        #[arg(long, num_args = 1.., required = true)]
+        versions: Vec<String>,
+
+        /// Pack the Mono versions with C# support.
+        #[arg(long)]
+        mono: bool,
+
+        /// Also pack the export templates.
+        #[arg(long)]
+        templates: bool,
+    },
+
+    /// Load a bundle into the cache, ready for `fyg install`.
+    Import {
+        /// The bundle to load. e.g. "engines.tar"
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Check a project's fyg.toml for errors without launching anything.
+    Check {
+        /// Path to a project directory that contains a fyg.toml file. If none specified, try the current directory.
+        project_dir: Option<PathBuf>,
+    },
+
+    /// Print the value of a setting in the user config.
+    Get {
+        /// Which setting to print. e.g. "default_version"
+        key: String,
+    },
+
+    /// Change a setting in the user config.
+    Set {
+        /// Which setting to change and its new value, as TOML or a plain string. e.g. "default_version 4.3"
+        ///
+        /// The value is taken as is even if it starts with a hyphen, so `launch_args --verbose`
+        /// sets Godot's flag rather than fyg's.
+        #[arg(required = true, num_args = 2, value_names = ["KEY", "VALUE"], trailing_var_arg = true, allow_hyphen_values = true)]
+        key_value: Vec<String>,
     },
+
+    /// List all settings in the user config.
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProjectsCommand {
+    /// List known projects and flag the ones whose engine is not installed.
+    List,
+
+    /// Remove projects from the list of known projects.
+    Forget {
+        /// Forget all projects whose directory no longer exists.
+        #[arg(long)]
+        missing: bool,
+
+        /// Which project directories to forget.
+        #[arg(required_unless_present = "missing")]
+        paths: Vec<PathBuf>,
+    },
+
+    /// Import projects from the project lists of installed engines.
+    Import,
 }
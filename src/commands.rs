@@ -1,78 +1,238 @@
 use std::{
     env, fs,
-    path::Path,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
 };
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 
 use crate::{
     cli::CliCommand,
-    platform::{PLATFORM, Platform},
+    engine::{self, Variant},
+    output::{CodedError, ErrorCode, Format},
     version::get_full_version,
 };
 
+mod bundle;
 mod cache;
+mod completions;
+mod config;
+mod dirs;
+mod doctor;
 mod edit;
+mod info;
+mod init;
 mod install;
 mod launch;
 mod list;
+mod lock;
+mod outdated;
+mod projects;
+mod prune;
+mod tui;
 mod uninstall;
+mod upgrade;
+mod vendor;
+mod which_config;
 
-pub fn get_binary_name(full_version: &str) -> String {
-    // TODO: The naming convention for binary/zip names seems to change a lot. To support all
-    // versions, might be best to use a static list that we generate.
-    let platform_suffix = if full_version.starts_with('4') {
-        match PLATFORM {
-            Platform::Windows32 => "win32.exe",
-            Platform::Windows64 => "win64.exe",
-            Platform::MacOS => "macos.universal",
-            Platform::Linux32 => "linux.x86_32",
-            Platform::Linux64 => "linux.x86_64",
-            Platform::Unsupported => "unsupported",
-        }
-    } else {
-        match PLATFORM {
-            Platform::Windows32 => "win32.exe",
-            Platform::Windows64 => "win64.exe",
-            Platform::MacOS => "osx.universal",
-            Platform::Linux32 => "x11.32",
-            Platform::Linux64 => "x11.64",
-            Platform::Unsupported => "unsupported",
+pub use completions::COMPLETE_VAR;
+
+/// The files and directories that make up one variant of an engine installed under `engines_dir`,
+/// including its export templates and editor settings. The Mono build is installed in a
+/// subdirectory of the standard build's directory, so each leaves the other alone. Empty if the
+/// variant isn't installed.
+fn engine_paths(engines_dir: &Path, full_version: &str, variant: Variant) -> Result<Vec<PathBuf>> {
+    let version_dir = engines_dir.join(full_version);
+    let mono_dir = engine::engine_dir_in(engines_dir, full_version, Variant::Mono);
+    match variant {
+        Variant::Mono if mono_dir.is_dir() => Ok(vec![mono_dir]),
+        Variant::Mono => Ok(Vec::new()),
+        Variant::Standard if !version_dir.is_dir() => Ok(Vec::new()),
+        Variant::Standard if !mono_dir.is_dir() => Ok(vec![version_dir]),
+        Variant::Standard => {
+            let mut paths = Vec::new();
+            for entry in fs::read_dir(&version_dir)? {
+                let path = entry?.path();
+                if path != mono_dir {
+                    paths.push(path);
+                }
+            }
+            paths.sort();
+            Ok(paths)
         }
-    };
-    format!("Godot_v{}_{}", &full_version, platform_suffix)
+    }
 }
 
-#[must_use]
-fn uninstall(engines_data_dir: &Path, version: &str) -> Result<()> {
+fn uninstall(engines_data_dir: &Path, version: &str, variant: Variant) -> Result<()> {
     let full_version = get_full_version(version);
-    let engine_path = engines_data_dir
-        .join(&full_version);
-    if engine_path.is_dir() {
-        fs::remove_dir_all(engine_path)?;
-        return Ok(());
+    let engine_paths = engine_paths(engines_data_dir, &full_version, variant)?;
+    if engine_paths.is_empty() {
+        let engine_path = engine::engine_dir_in(engines_data_dir, &full_version, variant);
+        let message = format!("Engine install dir \"{}\" does not exist.", engine_path.to_string_lossy());
+        return Err(anyhow!(CodedError::new(ErrorCode::NotInstalled, message)))
+            .context(format!("Could not uninstall version {}.", version));
+    }
+
+    for path in engine_paths {
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    // Remove the version's directory once neither variant is left in it.
+    let version_dir = engines_data_dir.join(&full_version);
+    if version_dir.is_dir() && fs::read_dir(&version_dir)?.next().is_none() {
+        fs::remove_dir(&version_dir)?;
+    }
+    Ok(())
+}
+
+/// Total size in bytes of all the files under `path`.
+fn dir_size(path: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// List the files or directories about to be removed with their sizes and the total, then check
+/// the user wants to go ahead. Returns false for a dry run or if the user says no.
+fn confirm_removal(paths: &[PathBuf], dry_run: bool, yes: bool) -> Result<bool> {
+    let mut total_size = 0;
+    for path in paths {
+        let size = if path.is_dir() {
+            dir_size(path)?
+        } else {
+            path.metadata()?.len()
+        };
+        println!("  {} ({})", path.display(), humansize::format_size(size, humansize::DECIMAL));
+        total_size += size;
     }
+    println!("Total: {}", humansize::format_size(total_size, humansize::DECIMAL));
 
-    Err(anyhow!("Engine install dir \"{}\" does not exist.", engine_path.to_string_lossy()))
-        .context(format!("Could not uninstall version {}.", version))
+    if dry_run {
+        return Ok(false);
+    }
+    let prompt = match paths.len() {
+        1 => "Remove it?".to_string(),
+        count => format!("Remove all {}?", count),
+    };
+    if !yes && !confirm(&prompt)? {
+        println!("Nothing removed.");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Ask the user a yes or no question. Anything but yes is no. Fails if there's no one to ask, so
+/// scripts have to opt in with --yes instead of hanging or silently going ahead.
+fn confirm(prompt: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        bail!("Can't ask for confirmation since input is not a terminal. Pass --yes to go ahead without asking.");
+    }
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
 }
 
-pub async fn run_command(command: &Option<CliCommand>) -> Result<()> {
+pub async fn run_command(command: &Option<CliCommand>, format: Format) -> Result<ExitCode> {
     let Some(command) = command else {
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     };
 
     match &command {
-        CliCommand::List { available } => list::cmd(*available).await,
-        CliCommand::Install { version, force } => install::cmd(version, *force).await,
-        CliCommand::Uninstall { version } => uninstall::cmd(version),
-        CliCommand::Launch { version } => launch::cmd(version),
-        CliCommand::Edit { project_dir } => {
+        CliCommand::List { available, prefix, regex, prereleases, stable_only, mono } => {
+            let filter = list::Filter::new(prefix.as_deref(), regex.as_deref(), *prereleases, *stable_only)?;
+            list::cmd(*available, &filter, Variant::from_mono(*mono), format).await
+        }
+        CliCommand::Install { version, mono, templates, force, from_file, sha512, sums } => {
+            match from_file {
+                Some(archive_path) => {
+                    install::from_file(archive_path, version.as_deref(), *mono, *force, sha512.as_deref(), sums.as_deref()).await
+                }
+                None => install::cmd(version.as_deref(), *mono, *templates, *force).await,
+            }
+        }
+        CliCommand::Uninstall { version, mono, dry_run, yes } => {
+            uninstall::cmd(version, Variant::from_mono(*mono), *dry_run, *yes)
+        }
+        CliCommand::Info { version } => info::cmd(version).await,
+        CliCommand::Launch { version, mono } => launch::cmd(version.as_deref(), Variant::from_mono(*mono)),
+        CliCommand::Edit { project_dir, install } => {
             let default_dir = env::current_dir()?;
             let project_dir = project_dir.as_ref()
                 .unwrap_or(&default_dir);
-            edit::cmd(project_dir)
+            edit::cmd(project_dir, *install).await
         }
-        CliCommand::Cache { cache_command } => cache::cmd(cache_command),
-    }
+        CliCommand::Cache { cache_command } => cache::cmd(cache_command, format),
+        CliCommand::Bundle { bundle_command } => bundle::cmd(bundle_command).await,
+        CliCommand::Init { version, force } => {
+            let project_fyg_dir = env::current_dir()?;
+            init::cmd(&project_fyg_dir, version.as_deref(), *force)
+        }
+        CliCommand::Config { config_command } => config::cmd(config_command),
+        CliCommand::Vendor { project_dir, templates, force } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            vendor::cmd(project_dir, *templates, *force).await
+        }
+        CliCommand::Lock { project_dir, update } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            lock::cmd(project_dir, *update).await
+        }
+        CliCommand::Tui => tui::cmd().await,
+        CliCommand::Completions { shell } => completions::cmd(*shell),
+        CliCommand::Dirs => dirs::cmd(format),
+        CliCommand::Doctor { project_dir } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            return doctor::cmd(project_dir, format).await;
+        }
+        CliCommand::WhichConfig { project_dir } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            which_config::cmd(project_dir)
+        }
+        CliCommand::Projects { projects_command } => projects::cmd(projects_command, format),
+        CliCommand::Prune { dry_run, cache, include_newest, yes } => {
+            prune::cmd(*dry_run, *cache, *include_newest, *yes)
+        }
+        CliCommand::Outdated { project_dir, check, json: _ } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            return outdated::cmd(project_dir, *check, format).await;
+        }
+        CliCommand::Upgrade { project_dir, to, yes } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            upgrade::cmd(project_dir, to.as_deref(), *yes).await
+        }
+        CliCommand::Sync { project_dir, force } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            install::sync(project_dir, *force).await
+        }
+    }?;
+
+    Ok(ExitCode::SUCCESS)
 }
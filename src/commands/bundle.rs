@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path};
+use std::time::SystemTime;
+
+use anyhow::{bail, Context, Result};
+use tracing::warn;
+
+use crate::{
+    cache,
+    checksum::{parse_sums, sha512_file},
+    cli::BundleCommand,
+    commands::install,
+    dirs::FygDirs,
+    engine::{self, Variant},
+    output::{CodedError, ErrorCode},
+    releases::{self, RELEASE_METADATA_NAME, SHA512_SUMS_NAME},
+    version::{get_full_version, Version},
+};
+
+pub async fn cmd(bundle_command: &BundleCommand) -> Result<()> {
+    match bundle_command {
+        BundleCommand::Create { path, versions, mono, templates } => {
+            create(path, versions, Variant::from_mono(*mono), *templates).await
+        }
+        BundleCommand::Import { path } => import(path),
+    }
+}
+
+/// Pack the given versions' archives into a tar laid out like the engines cache, with each
+/// version's sums and release metadata next to them.
+async fn create(bundle_path: &Path, versions: &[String], variant: Variant, templates: bool) -> Result<()> {
+    let bundle_file = fs::File::create(bundle_path)
+        .with_context(|| format!("Could not create {}.", bundle_path.display()))?;
+    let mut builder = tar::Builder::new(bundle_file);
+    if let Err(err) = append_versions(&mut builder, versions, variant, templates).await {
+        // Don't leave a partial bundle around to be mistaken for a complete one.
+        drop(builder);
+        let _ = fs::remove_file(bundle_path);
+        return Err(err);
+    }
+
+    builder.into_inner()?;
+    let bundle_size = bundle_path.metadata()?.len();
+    println!(
+        "Created {} ({}).",
+        bundle_path.display(),
+        humansize::format_size(bundle_size, humansize::DECIMAL),
+    );
+
+    Ok(())
+}
+
+async fn append_versions(builder: &mut tar::Builder<fs::File>, versions: &[String], variant: Variant, templates: bool) -> Result<()> {
+    for version in versions {
+        let version = version.trim();
+        let full_version = get_full_version(version);
+
+        // Download anything that's not cached yet, checking it against the published sums.
+        let mut asset_names = vec![engine::get_archive_name(&full_version, variant)];
+        if templates {
+            asset_names.push(engine::get_templates_name(&full_version, variant));
+        }
+        let mut assets = Vec::new();
+        let mut sums = String::new();
+        for asset_name in asset_names {
+            let (asset_path, sha512) = install::fetch_asset(version, &asset_name, None).await?;
+            sums.push_str(&format!("{}  {}\n", sha512, asset_name));
+            assets.push((asset_path, asset_name));
+        }
+
+        // Sums go first so import can check each archive as it's unpacked.
+        append_data(builder, &format!("{}/{}", full_version, SHA512_SUMS_NAME), sums.as_bytes())?;
+        match releases::get_release(&full_version).await {
+            Some(release) => {
+                let metadata = serde_json::to_vec_pretty(&release)?;
+                append_data(builder, &format!("{}/{}", full_version, RELEASE_METADATA_NAME), &metadata)?;
+            }
+            None => warn!(
+                "Could not get the release metadata for version {}, so `fyg info` won't show it after importing.",
+                version,
+            ),
+        }
+        for (asset_path, asset_name) in &assets {
+            builder.append_path_with_name(asset_path, format!("{}/{}", full_version, asset_name))
+                .with_context(|| format!("Could not add {} to the bundle.", asset_path.display()))?;
+        }
+        println!("Added version {} to the bundle.", version);
+    }
+
+    Ok(())
+}
+
+fn append_data(builder: &mut tar::Builder<fs::File>, path: &str, data: &[u8]) -> Result<()> {
+    let mtime = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs();
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// Unpack a bundle into the engines cache, checking each archive against the bundled sums.
+fn import(bundle_path: &Path) -> Result<()> {
+    let bundle_file = fs::File::open(bundle_path)
+        .with_context(|| format!("Could not open {}.", bundle_path.display()))?;
+    let mut archive = tar::Archive::new(bundle_file);
+    let engines_cache_dir = FygDirs::get().engines_cache();
+
+    let mut bundled_sums: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut imported_versions = Vec::new();
+    let entries = archive.entries()
+        .with_context(|| format!("Could not read {} as a bundle.", bundle_path.display()))?;
+    for entry in entries {
+        let mut entry = entry
+            .with_context(|| format!("Could not read {} as a bundle.", bundle_path.display()))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_path = entry.path()?.into_owned();
+        let Some((tag, name)) = split_bundle_path(&entry_path) else {
+            bail!(
+                "{} is not a fyg bundle. It contains {}.",
+                bundle_path.display(),
+                entry_path.display(),
+            );
+        };
+        let version_dir = engines_cache_dir.join(&tag);
+        fs::create_dir_all(&version_dir)?;
+        let cache_path = version_dir.join(&name);
+
+        if name == SHA512_SUMS_NAME {
+            let mut sums_str = String::new();
+            entry.read_to_string(&mut sums_str)?;
+            let sums = parse_sums(&sums_str);
+            merge_sums(&cache_path, &sums)?;
+            bundled_sums.insert(tag, sums);
+            continue;
+        }
+        if name == RELEASE_METADATA_NAME {
+            entry.unpack(&cache_path)?;
+            continue;
+        }
+
+        let Some(expected_sha512) = bundled_sums.get(&tag).and_then(|sums| sums.get(&name)) else {
+            bail!(CodedError::new(
+                ErrorCode::ChecksumMismatch,
+                format!("{} has no SHA512 sum for {}/{}.", bundle_path.display(), tag, name),
+            ));
+        };
+
+        // Only replace what's in the cache once the new file checks out.
+        let partial_path = version_dir.join(format!("{}.part", name));
+        entry.unpack(&partial_path)
+            .with_context(|| format!("Could not unpack {}.", entry_path.display()))?;
+        if &sha512_file(&partial_path)? != expected_sha512 {
+            fs::remove_file(&partial_path)?;
+            bail!(CodedError::new(
+                ErrorCode::ChecksumMismatch,
+                format!("{}/{} in the bundle does not match its SHA512 sum.", tag, name),
+            ));
+        }
+        fs::rename(&partial_path, &cache_path)?;
+        // Count importing as a use, so the archive isn't evicted straight away.
+        cache::touch(&cache_path)?;
+        println!("Imported {}", cache_path.display());
+
+        let version = tag.strip_suffix("-stable").unwrap_or(&tag).to_string();
+        if !imported_versions.contains(&version) {
+            imported_versions.push(version);
+        }
+    }
+
+    if imported_versions.is_empty() {
+        println!("Nothing to import.");
+    } else {
+        println!("Imported versions {}. Install them with `fyg install`.", imported_versions.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Split a path in a bundle into its release tag and file name. Anything else, including paths
+/// that would land outside the cache, isn't part of a bundle.
+fn split_bundle_path(path: &Path) -> Option<(String, String)> {
+    let mut components = path.components();
+    let (Some(Component::Normal(tag)), Some(Component::Normal(name)), None) =
+        (components.next(), components.next(), components.next()) else {
+        return None;
+    };
+    let tag = tag.to_str()?;
+    Version::parse(tag)?;
+    Some((tag.to_string(), name.to_str()?.to_string()))
+}
+
+/// Add sums to a sums file in the cache, keeping any it already has for other archives.
+fn merge_sums(sums_path: &Path, sums: &HashMap<String, String>) -> Result<()> {
+    let mut merged: BTreeMap<String, String> = match fs::read_to_string(sums_path) {
+        Ok(existing) => parse_sums(&existing).into_iter().collect(),
+        Err(_) => BTreeMap::new(),
+    };
+    merged.extend(sums.iter().map(|(name, sha512)| (name.clone(), sha512.clone())));
+    let sums_str: String = merged.iter()
+        .map(|(name, sha512)| format!("{}  {}\n", sha512, name))
+        .collect();
+    fs::write(sums_path, sums_str)
+        .with_context(|| format!("Could not write {}.", sums_path.display()))
+}
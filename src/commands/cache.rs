@@ -1,47 +1,67 @@
-use std::fs;
+use std::path::PathBuf;
 
 use anyhow::Result;
+use serde::Serialize;
 
 use crate::{
+    cache::{self, CacheEntry, CacheKind},
     cli::CacheCommand,
-    commands::get_binary_name,
-    dirs::FygDirs,
+    commands::confirm_removal,
+    config::UserConfig,
+    engine::Variant,
+    output::{self, Format},
 };
 
-pub fn cmd(cache_command: &Option<CacheCommand>) -> Result<()> {
-    let fyg_dirs = FygDirs::get();
+#[derive(Serialize)]
+struct CacheSummary<'a> {
+    entries: &'a [CacheEntry],
+    total_size: u64,
+}
 
+pub fn cmd(cache_command: &Option<CacheCommand>, format: Format) -> Result<()> {
     match cache_command {
-        Some(CacheCommand::Show) | None => {
-            if !fyg_dirs.engines_cache().is_dir() {
-                // Engines cache directory doesn't exist.
-                return Ok(());
+        Some(CacheCommand::Show) | None => show(format)?,
+        Some(CacheCommand::Gc { dry_run }) => gc(*dry_run)?,
+        Some(CacheCommand::Rm { all, versions, dry_run, yes }) => rm(*all, versions, *dry_run, *yes)?,
+    }
+
+    Ok(())
+}
+
+/// What a cache entry is, in words.
+pub fn kind_label(entry: &CacheEntry) -> &'static str {
+    match (entry.kind, entry.variant) {
+        (CacheKind::Engine, Some(Variant::Mono)) => "mono engine",
+        (CacheKind::Engine, _) => "engine",
+        (CacheKind::Templates, Some(Variant::Mono)) => "mono templates",
+        (CacheKind::Templates, _) => "templates",
+        (CacheKind::Sums, _) => "sums",
+        (CacheKind::Release, _) => "release metadata",
+        (CacheKind::Unknown, _) => "unknown",
+    }
+}
+
+fn show(format: Format) -> Result<()> {
+    let entries = cache::entries()?;
+    let total_size = entries.iter().map(|entry| entry.size).sum();
+
+    match format {
+        Format::Text => {
+            let (known, unknown): (Vec<_>, Vec<_>) = entries.iter()
+                .partition(|entry| entry.kind != CacheKind::Unknown);
+            for entry in known {
+                let kind = kind_label(entry);
+                let formatted_size = humansize::format_size(entry.size, humansize::DECIMAL);
+                let version = entry.version.as_deref().unwrap_or_default();
+                println!("{} ({}, {}): {}", version, kind, formatted_size, entry.path.display());
             }
 
-            let mut total_size = 0;
-
-            // List cached engine versions.
-            let read_dir = fs::read_dir(fyg_dirs.engines_cache())?;
-            for entry in read_dir {
-                let entry = entry?;
-                let version_path = entry.path();
-                if version_path.is_dir() {
-                    let file_name = entry.file_name();
-                    let full_version = file_name.to_string_lossy();
-                    let bin_name = get_binary_name(&full_version);
-                    let zip_name = format!("{}.zip", &bin_name);
-                    let zip_path = version_path
-                        .join(&zip_name);
-                    if zip_path.is_file() {
-                        let version = full_version.strip_suffix("-stable")
-                            .unwrap_or(&full_version);
-                        let metadata = zip_path.metadata()?;
-                        let byte_size = metadata.len();
-                        let formatted_size = humansize::format_size(byte_size, humansize::DECIMAL);
-                        println!("{} ({}): {}", &version, formatted_size, zip_path.display());
-
-                        total_size += byte_size;
-                    }
+            // Point out anything fyg doesn't know about rather than quietly counting it.
+            if !unknown.is_empty() {
+                println!("Unknown files:");
+                for entry in unknown {
+                    let formatted_size = humansize::format_size(entry.size, humansize::DECIMAL);
+                    println!("  {} ({})", entry.path.display(), formatted_size);
                 }
             }
 
@@ -49,32 +69,69 @@ pub fn cmd(cache_command: &Option<CacheCommand>) -> Result<()> {
             let formatted_size = humansize::format_size(total_size, humansize::DECIMAL);
             println!("Total: {}", formatted_size);
         }
-        Some(CacheCommand::Rm { all, versions }) => {
-            if *all {
-                // TODO: Collect all dirs to be removed, print them, and confirm removal.
-                let read_dir = fs::read_dir(fyg_dirs.engines_cache())?;
-                for entry in read_dir {
-                    let entry = entry?;
-                    let version_path = entry.path();
-                    println!("Removing {}", version_path.display());
-                    fs::remove_dir_all(version_path)?;
-                }
-                return Ok(());
-            }
+        Format::Json => {
+            let summary = CacheSummary {
+                entries: &entries,
+                total_size,
+            };
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        Format::Ndjson => output::print_records(format, &entries)?,
+    }
 
-            for version in versions {
-                let version = version.trim();
-                let version_path = fyg_dirs.engines_cache()
-                    .join(format!("{}-stable", version));
-                if version_path.is_dir() {
-                    println!("Removing {}", version_path.display());
-                    fs::remove_dir_all(version_path)?;
-                } else {
-                    println!("Cache for version \"{}\" not found", version);
-                }
+    Ok(())
+}
+
+fn rm(all: bool, versions: &[String], dry_run: bool, yes: bool) -> Result<()> {
+    // Collect everything to be removed first, so it can be shown before anything is removed.
+    let mut entries = cache::entries()?;
+    if !all {
+        for version in versions {
+            let version = version.trim();
+            if !entries.iter().any(|entry| entry.matches_version(version)) {
+                println!("Cache for version \"{}\" not found", version);
             }
         }
+        entries.retain(|entry| {
+            versions.iter().any(|version| entry.matches_version(version.trim()))
+        });
+    }
+
+    if entries.is_empty() {
+        println!("Nothing to remove.");
+        return Ok(());
+    }
+    println!("Cached downloads to remove:");
+    let paths: Vec<PathBuf> = entries.iter()
+        .map(|entry| entry.path.clone())
+        .collect();
+    if !confirm_removal(&paths, dry_run, yes)? {
+        return Ok(());
+    }
+
+    for entry in &entries {
+        println!("Removing {}", entry.path.display());
+        cache::remove(entry)?;
+    }
+
+    Ok(())
+}
+
+fn gc(dry_run: bool) -> Result<()> {
+    let user_config = UserConfig::get();
+    if user_config.cache_max_size.is_none() && user_config.cache_max_age_days.is_none() {
+        println!("No cache limits set. Set cache_max_size or cache_max_age_days with `fyg config set`.");
+        return Ok(());
+    }
+
+    let evicted = cache::collect_garbage(None, dry_run)?;
+    let (verb, freed) = if dry_run { ("Would remove", "Would free") } else { ("Removed", "Freed") };
+    for entry in &evicted {
+        let formatted_size = humansize::format_size(entry.size, humansize::DECIMAL);
+        println!("{} {} ({})", verb, entry.path.display(), formatted_size);
     }
+    let total_size = evicted.iter().map(|entry| entry.size).sum::<u64>();
+    println!("{}: {}", freed, humansize::format_size(total_size, humansize::DECIMAL));
 
     Ok(())
 }
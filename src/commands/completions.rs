@@ -0,0 +1,24 @@
+use std::io;
+
+use anyhow::Result;
+use clap_complete::env::{Bash, EnvCompleter, Fish, Powershell, Zsh};
+
+use crate::cli::Shell;
+
+/// Environment variable the registered script sets when asking fyg for completions.
+pub static COMPLETE_VAR: &str = "COMPLETE";
+
+static BIN_NAME: &str = "fyg";
+
+pub fn cmd(shell: Shell) -> Result<()> {
+    let completer: &dyn EnvCompleter = match shell {
+        Shell::Bash => &Bash,
+        Shell::Zsh => &Zsh,
+        Shell::Fish => &Fish,
+        Shell::Powershell => &Powershell,
+    };
+    // The script calls back into fyg with COMPLETE set, so versions are completed from whatever's
+    // installed or cached at the time.
+    completer.write_registration(COMPLETE_VAR, BIN_NAME, BIN_NAME, BIN_NAME, &mut io::stdout())?;
+    Ok(())
+}
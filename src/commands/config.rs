@@ -0,0 +1,150 @@
+use std::{env, fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Result};
+use tracing::warn;
+
+use crate::{
+    cli::ConfigCommand,
+    config::{closest_key, ProjectFygConfig, UserConfig, USER_CONFIG_KEYS},
+    dirs::user_config_path,
+    logging::status,
+    project::PROJECT_GODOT_NAME,
+};
+
+pub fn cmd(config_command: &ConfigCommand) -> Result<()> {
+    match config_command {
+        ConfigCommand::Check { project_dir } => {
+            let default_dir = env::current_dir()?;
+            let project_dir = project_dir.as_ref()
+                .unwrap_or(&default_dir);
+            check(project_dir)
+        }
+        ConfigCommand::Get { key } => get(key),
+        ConfigCommand::Set { key_value } => set(&key_value[0], &key_value[1]),
+        ConfigCommand::List => list(),
+    }
+}
+
+fn check(project_fyg_dir: &Path) -> Result<()> {
+    let config_paths = ProjectFygConfig::find_paths(project_fyg_dir);
+    let Some(config_path) = config_paths.first() else {
+        // Let load report which files it looked for.
+        ProjectFygConfig::load(project_fyg_dir)?;
+        return Ok(());
+    };
+
+    for warning in ProjectFygConfig::warnings(&config_paths) {
+        warn!("{}", warning);
+    }
+
+    check_project(project_fyg_dir, config_path)?;
+    status!("{} is valid.", config_path.display());
+
+    Ok(())
+}
+
+/// Load a project's config and check that it points at an actual Godot project.
+pub fn check_project(project_fyg_dir: &Path, config_path: &Path) -> Result<ProjectFygConfig> {
+    let project_config = ProjectFygConfig::load_file(config_path)?;
+
+    let godot_dir = project_config.godot_dir(project_fyg_dir);
+    if !godot_dir.is_dir() {
+        bail!("{}: root directory {} does not exist.", config_path.display(), godot_dir.display());
+    }
+    let project_godot_path = godot_dir.join(PROJECT_GODOT_NAME);
+    if !project_godot_path.is_file() {
+        bail!("{}: no {} file in {}.", config_path.display(), PROJECT_GODOT_NAME, godot_dir.display());
+    }
+
+    Ok(project_config)
+}
+
+fn check_key(key: &str) -> Result<()> {
+    if USER_CONFIG_KEYS.contains(&key) {
+        return Ok(());
+    }
+    match closest_key(key, USER_CONFIG_KEYS) {
+        Some(closest_key) => bail!("Unknown config key `{}`. Did you mean `{}`?", key, closest_key),
+        None => bail!("Unknown config key `{}`. Expected one of: {}", key, USER_CONFIG_KEYS.join(", ")),
+    }
+}
+
+fn get(key: &str) -> Result<()> {
+    check_key(key)?;
+
+    let user_config = UserConfig::load(&user_config_path().0)?;
+    let user_config_table = toml::Table::try_from(&user_config)?;
+    match user_config_table.get(key) {
+        // Print strings without quotes so they're easy to use in scripts.
+        Some(toml::Value::String(value)) => println!("{}", value),
+        Some(value) => println!("{}", value),
+        None => bail!("{} is not set.", key),
+    }
+
+    Ok(())
+}
+
+fn set(key: &str, value: &str) -> Result<()> {
+    check_key(key)?;
+
+    let user_config_path = user_config_path().0;
+    let user_config_str = if user_config_path.is_file() {
+        fs::read_to_string(&user_config_path)
+            .with_context(|| format!("Could not read {}.", user_config_path.display()))?
+    } else {
+        String::new()
+    };
+    // Edit the document in place to keep the user's formatting and comments.
+    let mut doc = user_config_str.parse::<toml_edit::DocumentMut>()
+        .with_context(|| format!("Could not parse {}.", user_config_path.display()))?;
+
+    // Take the value as TOML if that fits the setting, otherwise as a plain string or a list of
+    // one string. e.g. 4.3 is a version, not a float.
+    let mut candidates = Vec::new();
+    if let Ok(typed_value) = value.parse::<toml_edit::Value>() {
+        candidates.push(typed_value);
+    }
+    candidates.push(toml_edit::Value::from(value));
+    candidates.push(toml_edit::Value::from_iter([value]));
+
+    // Report why the closest fit was rejected, rather than the one-element list.
+    let mut first_err = None;
+    for candidate in candidates {
+        doc[key] = toml_edit::value(candidate);
+        let new_config_str = doc.to_string();
+        match UserConfig::parse(&new_config_str, &user_config_path) {
+            Ok(_) => {
+                if let Some(parent) = user_config_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&user_config_path, new_config_str)
+                    .with_context(|| format!("Could not write {}.", user_config_path.display()))?;
+                status!("Set {} = {}", key, doc[key].to_string().trim());
+                return Ok(());
+            }
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+
+    Err(first_err.unwrap_or_else(|| anyhow!("Invalid value for {}.", key)))
+}
+
+fn list() -> Result<()> {
+    let user_config_path = user_config_path().0;
+    let user_config = UserConfig::load(&user_config_path)?;
+    let user_config_table = toml::Table::try_from(&user_config)?;
+
+    println!("# {}", user_config_path.display());
+    for &key in USER_CONFIG_KEYS {
+        match user_config_table.get(key) {
+            // Don't leak the token into logs or screen shares.
+            Some(_) if key == "github_token" => println!("{} = \"<hidden>\"", key),
+            Some(value) => println!("{} = {}", key, value),
+            None => println!("# {} is not set", key),
+        }
+    }
+
+    Ok(())
+}
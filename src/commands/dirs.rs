@@ -0,0 +1,39 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    dirs::{user_config_path, DirSource, FygDirs},
+    output::{self, Format},
+};
+
+/// One of fyg's directories, as printed by `dirs --format json`.
+#[derive(Serialize)]
+struct DirRecord<'a> {
+    name: &'a str,
+    path: &'a Path,
+    source: DirSource,
+}
+
+pub fn cmd(format: Format) -> Result<()> {
+    let fyg_dirs = FygDirs::get();
+    let (config_path, config_source) = user_config_path();
+    let (data_dir, data_source) = fyg_dirs.data();
+    let (cache_dir, cache_source) = fyg_dirs.cache();
+    let records = [
+        DirRecord { name: "config", path: &config_path, source: config_source },
+        DirRecord { name: "data", path: data_dir, source: data_source },
+        DirRecord { name: "cache", path: cache_dir, source: cache_source },
+    ];
+
+    if format != Format::Text {
+        return output::print_records(format, &records);
+    }
+
+    for record in &records {
+        println!("{:<8}{} ({})", format!("{}:", record.name), record.path.display(), record.source);
+    }
+
+    Ok(())
+}
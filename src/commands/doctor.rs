@@ -0,0 +1,462 @@
+use std::{
+    cmp::Reverse,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, ExitCode, Stdio},
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use sysinfo::Disks;
+
+use crate::{
+    commands::config::check_project,
+    config::{ProjectFygConfig, UserConfig},
+    dirs::{user_config_path, FygDirs, FYG_CACHE_DIR_VAR, FYG_DATA_DIR_VAR, FYG_HOME_VAR},
+    engine::{self, InstallReceipt, Variant},
+    lock::ProjectLock,
+    output::{self, Format},
+    version::{get_full_version, Version},
+};
+
+/// Exit status when `doctor` finds problems. Errors exit with 1, so CI can tell the two apart.
+const PROBLEMS_EXIT_CODE: u8 = 2;
+
+/// Warn when a directory's disk has less room than an engine and its export templates take up.
+const LOW_FREE_SPACE: u64 = 2_000_000_000;
+
+/// Warn when fewer GitHub API requests than this are left before the limit resets.
+const LOW_RATE_LIMIT: usize = 10;
+
+/// How long to wait for GitHub or an engine's `--version` before giving up on it.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Warning,
+    Problem,
+}
+
+/// The result of one check, as printed by `doctor --format json`.
+#[derive(Serialize)]
+struct Check {
+    /// What was checked: "dirs", "github", "engines", "cache" or "project".
+    section: &'static str,
+    status: Status,
+    message: String,
+    /// What to do about a warning or problem.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(section: &'static str, message: String) -> Check {
+        Check { section, status: Status::Ok, message, fix: None }
+    }
+
+    fn warning(section: &'static str, message: String, fix: String) -> Check {
+        Check { section, status: Status::Warning, message, fix: Some(fix) }
+    }
+
+    fn problem(section: &'static str, message: String, fix: String) -> Check {
+        Check { section, status: Status::Problem, message, fix: Some(fix) }
+    }
+}
+
+pub async fn cmd(project_fyg_dir: &Path, format: Format) -> Result<ExitCode> {
+    let mut checks = Vec::new();
+    check_dirs(&mut checks);
+    check_github(&mut checks).await;
+    check_engines(&mut checks, FygDirs::get().engines_data(), false).await?;
+    check_cache(&mut checks)?;
+    check_project_dir(&mut checks, project_fyg_dir).await?;
+
+    let problem_count = checks.iter().filter(|check| check.status == Status::Problem).count();
+    let warning_count = checks.iter().filter(|check| check.status == Status::Warning).count();
+    if format == Format::Text {
+        print_checks(&checks);
+        println!();
+        match (problem_count, warning_count) {
+            (0, 0) => println!("No problems found."),
+            (problems, warnings) => println!("Found {} problem(s) and {} warning(s).", problems, warnings),
+        }
+    } else {
+        output::print_records(format, &checks)?;
+    }
+
+    if problem_count > 0 {
+        return Ok(ExitCode::from(PROBLEMS_EXIT_CODE));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_checks(checks: &[Check]) {
+    let mut section = "";
+    for check in checks {
+        if check.section != section {
+            section = check.section;
+            let title = match section {
+                "dirs" => "Directories",
+                "github" => "GitHub",
+                "engines" => "Engines",
+                "cache" => "Cache",
+                _ => "Project",
+            };
+            println!("{}", title.bold());
+        }
+        let status = format!("{:<8}", format!("{:?}", check.status).to_lowercase());
+        match check.status {
+            Status::Ok => println!("  {}{}", status.green(), check.message),
+            Status::Warning => println!("  {}{}", status.yellow(), check.message),
+            Status::Problem => println!("  {}{}", status.red(), check.message),
+        }
+        if let Some(fix) = &check.fix {
+            println!("          Fix: {}", fix);
+        }
+    }
+}
+
+/// Check that fyg can write to each of its directories, and that their disks have room.
+fn check_dirs(checks: &mut Vec<Check>) {
+    let fyg_dirs = FygDirs::get();
+    let (config_path, _) = user_config_path();
+    let config_dir = config_path.parent()
+        .unwrap_or(&config_path);
+    let dirs = [
+        ("config", config_dir, FYG_HOME_VAR),
+        ("data", fyg_dirs.data().0, FYG_DATA_DIR_VAR),
+        ("cache", fyg_dirs.cache().0, FYG_CACHE_DIR_VAR),
+    ];
+    let disks = Disks::new_with_refreshed_list();
+
+    for (name, dir, var) in dirs {
+        // A directory that doesn't exist yet is fine as long as it can be created.
+        let Some(existing_dir) = dir.ancestors().find(|ancestor| ancestor.is_dir()) else {
+            checks.push(Check::problem(
+                "dirs",
+                format!("{}: {} has no existing parent directory.", name, dir.display()),
+                format!("Point fyg at another directory with {}.", var),
+            ));
+            continue;
+        };
+        if let Err(err) = check_writable(existing_dir) {
+            checks.push(Check::problem(
+                "dirs",
+                format!("{}: Can't write to {}. {}", name, existing_dir.display(), err),
+                format!("Make {} writable, or point fyg at another directory with {}.", existing_dir.display(), var),
+            ));
+            continue;
+        }
+
+        let mut message = format!("{}: {}", name, dir.display());
+        if existing_dir != dir {
+            message.push_str(" (will be created)");
+        }
+        let disk = existing_dir.canonicalize()
+            .ok()
+            .and_then(|path| {
+                disks.iter()
+                    .filter(|disk| path.starts_with(disk.mount_point()))
+                    .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            });
+        let Some(disk) = disk else {
+            checks.push(Check::ok("dirs", message));
+            continue;
+        };
+        let free_space = humansize::format_size(disk.available_space(), humansize::DECIMAL);
+        message.push_str(&format!(
+            ", on {} at {} with {} free",
+            disk.file_system().to_string_lossy(),
+            disk.mount_point().display(),
+            free_space,
+        ));
+        if name != "config" && disk.available_space() < LOW_FREE_SPACE {
+            checks.push(Check::warning(
+                "dirs",
+                message,
+                format!(
+                    "Engines and their export templates can take over {}. Free up space, or point fyg at another disk with {}.",
+                    humansize::format_size(LOW_FREE_SPACE, humansize::DECIMAL),
+                    var,
+                ),
+            ));
+        } else {
+            checks.push(Check::ok("dirs", message));
+        }
+    }
+}
+
+/// Write and remove a file in a directory, since permissions alone don't cover read-only mounts.
+fn check_writable(dir: &Path) -> io::Result<()> {
+    let probe_path = dir.join(format!(".fyg-doctor-{}", process::id()));
+    fs::write(&probe_path, b"")?;
+    fs::remove_file(&probe_path)
+}
+
+/// Check that GitHub can be reached, and how many API requests are left.
+async fn check_github(checks: &mut Vec<Check>) {
+    let has_token = UserConfig::get().github_token.is_some();
+    let has_mirrors = !UserConfig::get().mirrors.is_empty();
+    let rate_limit = tokio::time::timeout(TIMEOUT, octocrab::instance().ratelimit().get()).await;
+    let rate_limit = match rate_limit {
+        Ok(Ok(rate_limit)) => rate_limit,
+        Ok(Err(err)) => {
+            // octocrab's errors can end with a backtrace, which isn't much help here.
+            let reason = err.to_string().lines().next().unwrap_or_default().to_string();
+            push_unreachable(checks, reason, has_mirrors);
+            return;
+        }
+        Err(_) => {
+            push_unreachable(checks, format!("No response within {} seconds.", TIMEOUT.as_secs()), has_mirrors);
+            return;
+        }
+    };
+
+    let core = rate_limit.resources.core;
+    let reset = DateTime::from_timestamp(core.reset as i64, 0)
+        .map(|reset| reset.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_default();
+    let message = format!(
+        "Reachable, with {} of {} API requests left until {}.",
+        core.remaining,
+        core.limit,
+        reset,
+    );
+    let fix = if has_token {
+        format!("Wait until {} for the limit to reset.", reset)
+    } else {
+        "Set a GitHub token with `fyg config set github_token <TOKEN>` for a higher limit.".to_string()
+    };
+    match core.remaining {
+        0 => checks.push(Check::problem("github", message, fix)),
+        remaining if remaining < LOW_RATE_LIMIT => checks.push(Check::warning("github", message, fix)),
+        _ => checks.push(Check::ok("github", message)),
+    }
+}
+
+fn push_unreachable(checks: &mut Vec<Check>, reason: String, has_mirrors: bool) {
+    let message = format!("Could not reach GitHub. {}", reason);
+    if has_mirrors {
+        // Downloads can still come from the user's mirrors.
+        checks.push(Check::warning(
+            "github",
+            message,
+            "Downloads will use your mirrors, but new releases can't be listed. Check your network or proxy settings.".to_string(),
+        ));
+    } else {
+        checks.push(Check::problem(
+            "github",
+            message,
+            "Check your network or proxy settings. Without internet access, use `fyg bundle` or `fyg install --from-file`.".to_string(),
+        ));
+    }
+}
+
+/// Check that each engine under `engines_dir` has a binary that runs. A project's vendored engines
+/// are reported with the project, since they're re-installed with `fyg vendor`.
+async fn check_engines(checks: &mut Vec<Check>, engines_dir: &Path, vendored: bool) -> Result<()> {
+    let section = if vendored { "project" } else { "engines" };
+    if !engines_dir.is_dir() {
+        checks.push(Check::ok(section, format!("None installed in {}.", engines_dir.display())));
+        return Ok(());
+    }
+
+    let mut version_dirs: Vec<PathBuf> = fs::read_dir(engines_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    version_dirs.retain(|path| path.is_dir());
+    version_dirs.sort_by_key(|path| {
+        Reverse(Version::parse(&path.file_name().unwrap_or_default().to_string_lossy()))
+    });
+
+    for version_dir in version_dirs {
+        let full_version = version_dir.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let version = full_version.strip_suffix("-stable").unwrap_or(&full_version);
+        let mut found_binary = false;
+        for variant in Variant::ALL {
+            let bin_path = engine::bin_path_in(engines_dir, &full_version, variant);
+            if !bin_path.is_file() {
+                continue;
+            }
+            found_binary = true;
+            let name = match variant {
+                Variant::Standard => version.to_string(),
+                Variant::Mono => format!("{} (mono)", version),
+            };
+            let fix = if vendored {
+                "Re-install it with `fyg vendor --force`.".to_string()
+            } else {
+                let mono_flag = if variant == Variant::Mono { " --mono" } else { "" };
+                format!("Re-install it with `fyg install {}{} --force`.", version, mono_flag)
+            };
+            // The receipt is written once the engine is fully extracted, so one without a receipt
+            // may be missing files, unless an older fyg installed it.
+            let engine_dir = engine::engine_dir_in(engines_dir, &full_version, variant);
+            if InstallReceipt::load(&engine_dir)?.is_none() {
+                checks.push(Check::warning(
+                    section,
+                    format!(
+                        "{}: {} has no install receipt, so it may be left over from an interrupted install.",
+                        name,
+                        engine_dir.display(),
+                    ),
+                    fix.clone(),
+                ));
+            }
+            checks.push(match run_version(&bin_path, &full_version).await {
+                Ok(output) => Check::ok(section, format!("{}: {}", name, output)),
+                Err(reason) => Check::problem(section, format!("{}: {}", name, reason), fix),
+            });
+        }
+
+        // An engine directory without an engine is left over from an interrupted install.
+        if !found_binary {
+            let install_command = if vendored { "fyg vendor".to_string() } else { format!("fyg install {}", version) };
+            checks.push(Check::problem(
+                section,
+                format!("{}: {} has no engine binary.", version, version_dir.display()),
+                format!("Remove it, then re-install with `{}`.", install_command),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Run an engine's `--version` without opening a window, returning what it printed or what went
+/// wrong.
+pub async fn run_version(bin_path: &Path, full_version: &str) -> Result<String, String> {
+    if !is_executable(bin_path) {
+        return Err(format!("{} is not executable.", bin_path.display()));
+    }
+
+    let mut command = tokio::process::Command::new(bin_path);
+    // Godot 3 doesn't know --headless, but also doesn't open a window just for --version.
+    if !full_version.starts_with('3') {
+        command.arg("--headless");
+    }
+    command.arg("--version")
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+    let output = match tokio::time::timeout(TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(err)) => return Err(format!("Could not run {}. {}", bin_path.display(), err)),
+        Err(_) => return Err(format!("`--version` did not finish within {} seconds.", TIMEOUT.as_secs())),
+    };
+    if !output.status.success() {
+        return Err(format!("`--version` failed with {}.", output.status));
+    }
+    // The version is on the last line, after any warnings the engine printed while starting.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().map(str::trim).rfind(|line| !line.is_empty()).unwrap_or_default().to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Look for partial files left in the cache by an interrupted download or `fyg bundle import`.
+fn check_cache(checks: &mut Vec<Check>) -> Result<()> {
+    let engines_cache_dir = FygDirs::get().engines_cache();
+    let mut partial_paths = Vec::new();
+    if engines_cache_dir.is_dir() {
+        for version_entry in fs::read_dir(engines_cache_dir)? {
+            let version_path = version_entry?.path();
+            if !version_path.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&version_path)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|extension| extension == "part") {
+                    partial_paths.push(path);
+                }
+            }
+        }
+    }
+
+    if partial_paths.is_empty() {
+        checks.push(Check::ok("cache", "No partial downloads.".to_string()));
+    }
+    for partial_path in partial_paths {
+        checks.push(Check::warning(
+            "cache",
+            format!("{} is left over from an interrupted download or import.", partial_path.display()),
+            "Remove it, then install the version or import the bundle again.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the project in `project_fyg_dir`, if there is one: its config, lock and engine.
+async fn check_project_dir(checks: &mut Vec<Check>, project_fyg_dir: &Path) -> Result<()> {
+    let config_paths = ProjectFygConfig::find_paths(project_fyg_dir);
+    let Some(config_path) = config_paths.first() else {
+        checks.push(Check::ok("project", format!("No fyg config in {}.", project_fyg_dir.display())));
+        return Ok(());
+    };
+
+    for warning in ProjectFygConfig::warnings(&config_paths) {
+        checks.push(Check::warning("project", warning, "Remove the config file that's ignored.".to_string()));
+    }
+
+    let project_config = match check_project(project_fyg_dir, config_path) {
+        Ok(project_config) => project_config,
+        Err(err) => {
+            checks.push(Check::problem(
+                "project",
+                format!("{:#}", err),
+                format!("Fix {}, then check it with `fyg config check`.", config_path.display()),
+            ));
+            return Ok(());
+        }
+    };
+    checks.push(Check::ok("project", format!("{} is valid.", config_path.display())));
+
+    match ProjectLock::load(project_fyg_dir).and_then(|lock| {
+        lock.map(|lock| lock.check(&project_config)).transpose()
+    }) {
+        Ok(Some(())) => checks.push(Check::ok("project", "fyg.lock matches the config.".to_string())),
+        Ok(None) => {}
+        Err(err) => checks.push(Check::problem(
+            "project",
+            format!("{:#}", err),
+            "Run `fyg lock --update` to pin the config's version again.".to_string(),
+        )),
+    }
+
+    // The project's engine is checked with the others unless it's vendored.
+    let full_version = get_full_version(&project_config.version);
+    let engines_dir = project_config.engines_dir(project_fyg_dir);
+    let install_command = if project_config.vendor { "fyg vendor" } else { "fyg sync" };
+    if engine::bin_path_in(&engines_dir, &full_version, project_config.variant()).is_file() {
+        if project_config.vendor {
+            check_engines(checks, &engines_dir, true).await?;
+        }
+    } else {
+        checks.push(Check::warning(
+            "project",
+            format!("Godot {} is not installed for this project.", project_config.version),
+            format!("Run `{}` to install it.", install_command),
+        ));
+    }
+
+    Ok(())
+}
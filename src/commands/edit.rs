@@ -4,27 +4,23 @@ use std::{
 };
 
 use anyhow::{bail, Result};
+use chrono::Utc;
+use tracing::warn;
 
 use crate::{
-    commands::get_binary_name,
-    config::ProjectFygConfig,
-    dirs::FygDirs,
+    commands::install,
+    config::{ProjectFygConfig, UserConfig},
+    engine,
+    lock::ProjectLock,
+    output::{CodedError, ErrorCode},
+    project::{ProjectGodot, PROJECT_GODOT_NAME},
+    registry::{KnownProject, ProjectRegistry},
     version::get_full_version,
 };
 
-static PROJECT_GODOT_NAME: &str = "project.godot";
-
-pub fn cmd(project_fyg_dir: &Path) -> Result<()> {
+pub async fn cmd(project_fyg_dir: &Path, install: bool) -> Result<()> {
     let project_config = ProjectFygConfig::load(project_fyg_dir)?;
-    let godot_dir = if let Some(dir) = &project_config.root {
-        if dir.is_relative() {
-            project_fyg_dir.join(dir)
-        } else {
-            dir.clone()
-        }
-    } else {
-        project_fyg_dir.to_owned()
-    };
+    let godot_dir = project_config.godot_dir(project_fyg_dir);
 
     // Check for project.godot in this directory.
     let project_godot_path = godot_dir.join(PROJECT_GODOT_NAME);
@@ -32,27 +28,78 @@ pub fn cmd(project_fyg_dir: &Path) -> Result<()> {
         bail!("No {} file in {}.", PROJECT_GODOT_NAME, godot_dir.display());
     }
 
-    let fyg_dirs = FygDirs::get();
+    // Install anything the project needs that's missing.
+    if install {
+        install::install_project(project_fyg_dir, &project_config, false).await?;
+    }
 
-    // Check that the project's Godot version is installed.
-    let full_version = get_full_version(&project_config.version);
-    let bin_name = get_binary_name(&full_version);
-    let bin_path = fyg_dirs.engines_data()
-        .join(&full_version)
-        .join(bin_name);
+    // Use the exact release in the project's lock, if it has one.
+    let lock = ProjectLock::load(project_fyg_dir)?;
+    if let Some(lock) = &lock {
+        lock.check(&project_config)?;
+    }
+    let version = lock.as_ref()
+        .map_or(&project_config.version, |lock| &lock.tag);
+
+    // Check that the project's Godot version is installed, preferring a vendored engine.
+    let variant = project_config.variant();
+    let full_version = get_full_version(version);
+    let vendored_engines_dir = engine::vendored_engines_dir(project_fyg_dir);
+    let engines_dir = if engine::bin_path_in(&vendored_engines_dir, &full_version, variant).is_file() {
+        vendored_engines_dir
+    } else {
+        project_config.engines_dir(project_fyg_dir)
+    };
+    let bin_path = engine::bin_path_in(&engines_dir, &full_version, variant);
     if !bin_path.is_file() {
-        bail!("Can't edit project. Godot version {} is not installed.", &project_config.version);
+        let install_command = if project_config.vendor { "fyg vendor" } else { "fyg install" };
+        bail!(CodedError::new(
+            ErrorCode::NotInstalled,
+            format!(
+                "Can't edit project. Godot version {} is not installed. Run `{}` or pass --install to install it.",
+                &project_config.version,
+                install_command,
+            ),
+        ));
+    }
+    if let Some(lock) = &lock {
+        lock.verify_installed(&engines_dir, variant)?;
     }
 
     // Run Godot with the given project!!
     println!("Editing project with: {}", bin_path.to_string_lossy());
     Command::new(&bin_path)
         .arg("--editor")
-        .arg(project_godot_path)
+        .arg(&project_godot_path)
+        .args(&UserConfig::get().launch_args)
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()?;
 
+    // Remember this project for `fyg projects`. Not being able to shouldn't stop the edit.
+    if let Err(err) = record_project(project_fyg_dir, &project_config, &project_godot_path) {
+        warn!("Could not record project. {:#}", err);
+    }
+
     Ok(())
 }
+
+fn record_project(project_fyg_dir: &Path, project_config: &ProjectFygConfig, project_godot_path: &Path) -> Result<()> {
+    let mut registry = ProjectRegistry::load()?;
+    let project_godot = ProjectGodot::load(project_godot_path)?;
+    let path = project_fyg_dir.canonicalize()?;
+    let godot_dir = project_godot_path.parent()
+        .map(Path::canonicalize)
+        .transpose()?
+        .filter(|godot_dir| *godot_dir != path);
+    registry.insert(KnownProject {
+        path,
+        godot_dir,
+        name: project_godot.name,
+        version: project_config.version.clone(),
+        mono: project_config.mono,
+        last_opened: Some(Utc::now()),
+    });
+    registry.save()
+}
@@ -0,0 +1,122 @@
+use std::sync::LazyLock;
+
+use anyhow::{bail, Result};
+use octocrab::models::repos::Release;
+use owo_colors::OwoColorize;
+use regex::{Captures, Regex};
+
+use crate::{
+    commands::{dir_size, doctor::run_version},
+    engine::{self, Variant},
+    output::{CodedError, ErrorCode},
+    releases,
+    version::get_full_version,
+};
+
+pub async fn cmd(version: &str) -> Result<()> {
+    let full_version = get_full_version(version);
+    let installed_variants: Vec<_> = Variant::ALL.into_iter()
+        .filter(|variant| engine::bin_path(&full_version, *variant).is_file())
+        .collect();
+    let release = releases::get_release(&full_version).await;
+    if release.is_none() && installed_variants.is_empty() {
+        bail!(CodedError::new(ErrorCode::NotFound, format!("Version {} not found.", version)));
+    }
+
+    println!("{}", format!("Godot {} ({})", version, full_version).bold());
+
+    for variant in installed_variants {
+        println!();
+        print_install(&full_version, variant).await?;
+    }
+
+    println!();
+    match &release {
+        Some(release) => print_release(release, &full_version),
+        None => println!("Could not find the {} release on GitHub.", full_version),
+    }
+
+    Ok(())
+}
+
+async fn print_install(full_version: &str, variant: Variant) -> Result<()> {
+    let engine_dir = engine::engine_dir(full_version, variant);
+    let bin_path = engine::bin_path(full_version, variant);
+    let size = humansize::format_size(dir_size(&engine_dir)?, humansize::DECIMAL);
+    let self_contained = if engine::is_self_contained(full_version, variant) { "yes" } else { "no" };
+
+    println!("Installed ({}):", variant);
+    println!("  Path: {}", bin_path.display());
+    println!("  Size: {}", size);
+    println!("  Self-contained: {}", self_contained);
+    // The engine's exact build. e.g. "4.3.stable.official.77dcf97d8"
+    match run_version(&bin_path, full_version).await {
+        Ok(build) => println!("  Build: {}", build),
+        Err(reason) => println!("  Build: unknown ({})", reason),
+    }
+    Ok(())
+}
+
+fn print_release(release: &Release, full_version: &str) {
+    println!("Release:");
+    if let Some(published_at) = release.published_at {
+        println!("  Published: {}", published_at.format("%Y-%m-%d"));
+    }
+    println!("  URL: {}", release.html_url);
+
+    // Downloads relevant to this platform: the engine archives and the export templates.
+    let platform_assets: Vec<_> = Variant::ALL.into_iter()
+        .flat_map(|variant| [
+            engine::get_archive_name(full_version, variant),
+            engine::get_templates_name(full_version, variant),
+        ])
+        .collect();
+    let has_asset = |name: &str| release.assets.iter().any(|asset| asset.name == name);
+    let yes_no = |available: bool| if available { "yes" } else { "no" };
+    println!("  Mono: {}", yes_no(has_asset(&engine::get_archive_name(full_version, Variant::Mono))));
+    println!("  Export templates: {}", yes_no(has_asset(&engine::get_templates_name(full_version, Variant::Standard))));
+
+    println!("  Downloads for this platform:");
+    let mut any_asset = false;
+    for asset in release.assets.iter().filter(|asset| platform_assets.contains(&asset.name)) {
+        let size = humansize::format_size(u64::try_from(asset.size).unwrap_or(0), humansize::DECIMAL);
+        println!("    {} ({})", asset.name, size);
+        any_asset = true;
+    }
+    if !any_asset {
+        println!("    {}", "None".dimmed());
+    }
+
+    if let Some(body) = release.body.as_deref().filter(|body| !body.trim().is_empty()) {
+        println!();
+        println!("Release notes:");
+        for line in body.lines() {
+            println!("  {}", render_markdown_line(line));
+        }
+    }
+}
+
+/// Make a line of a release's Markdown notes easier to read in a terminal. Only handles what release
+/// notes commonly use: headings, bullet points, bold text and links.
+fn render_markdown_line(line: &str) -> String {
+    static LINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\]]*)\]\(([^)]*)\)").unwrap());
+    static BOLD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*([^*]+)\*\*").unwrap());
+
+    let line = line.trim_end();
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+        return trimmed.trim_start_matches('#').trim().bold().to_string();
+    }
+
+    // Keep any indentation so nested bullet points stay nested.
+    let indent = &line[..line.len() - trimmed.len()];
+    let line = match trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+        Some(item) => format!("{}• {}", indent, item),
+        None => line.to_string(),
+    };
+    let line = LINK.replace_all(&line, "$1 ($2)");
+    BOLD.replace_all(&line, |captures: &Captures| {
+        let text = &captures[1];
+        text.bold().to_string()
+    }).into_owned()
+}
@@ -0,0 +1,105 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use tracing::warn;
+
+use crate::{
+    config::ProjectFygConfig,
+    project::{find_project_godot, ProjectGodot, PROJECT_GODOT_NAME},
+};
+
+static FYG_CONFIG_NAME: &str = "fyg.toml";
+
+/// The config file fyg.toml replaced, which still counts as the project's own config.
+static OLD_FYG_CONFIG_NAME: &str = "godot_version.toml";
+
+pub fn cmd(project_fyg_dir: &Path, version: Option<&str>, force: bool) -> Result<()> {
+    // Don't clobber an existing config unless asked to. Other tools' version files are never
+    // overwritten, since fyg.toml just takes precedence over them.
+    let config_paths = ProjectFygConfig::find_paths(project_fyg_dir);
+    if let Some(existing_path) = config_paths.iter().find(|path| is_fyg_config(path)) {
+        if !force {
+            bail!("{} already exists. Pass --force to overwrite it.", existing_path.display());
+        }
+    }
+
+    // Keep the version another tool's file already pins, rather than detecting it again.
+    let seed_path = config_paths.first()
+        .filter(|path| !is_fyg_config(path));
+    let seed_config = seed_path
+        .map(|path| ProjectFygConfig::load_file(path))
+        .transpose()?;
+
+    let Some(project_godot_path) = find_project_godot(project_fyg_dir)? else {
+        bail!("No {} found in or below {}.", PROJECT_GODOT_NAME, project_fyg_dir.display());
+    };
+
+    let project_godot = ProjectGodot::load(&project_godot_path)?;
+    let version_given = version.is_some();
+    let version = match (version, &seed_config) {
+        (Some(version), _) => version.to_string(),
+        (None, Some(seed_config)) => seed_config.version.clone(),
+        (None, None) => {
+            project_godot.engine_version()
+                .ok_or_else(|| anyhow!(
+                    "Could not detect the engine version from {}. Pass --version to set it.",
+                    project_godot_path.display(),
+                ))?
+                .to_string()
+        }
+    };
+
+    // Point root at project.godot's directory, relative to the fyg.toml like edit expects.
+    let godot_dir = project_godot_path.parent()
+        .unwrap_or(project_fyg_dir);
+    let root = godot_dir.strip_prefix(project_fyg_dir)
+        .ok()
+        .and_then(to_portable_path);
+
+    let config = ProjectFygConfig {
+        version,
+        root,
+        mono: project_godot.uses_csharp() || seed_config.as_ref().is_some_and(|seed_config| seed_config.mono),
+        templates: false,
+        vendor: false,
+    };
+    let config_path = project_fyg_dir.join(FYG_CONFIG_NAME);
+    config.save(&config_path)?;
+
+    println!("Wrote {} with version {}.", config_path.display(), &config.version);
+    if let Some(seed_path) = seed_path.filter(|_| !version_given) {
+        println!("Took the version from {}.", seed_path.display());
+    }
+    if let Some(root) = &config.root {
+        println!("Project root: {}", root.display());
+    }
+
+    // Other config files are left alone, but they no longer decide the version.
+    for ignored_path in ProjectFygConfig::find_paths(project_fyg_dir).iter().skip(1) {
+        warn!("{} is ignored since {} takes precedence.", ignored_path.display(), FYG_CONFIG_NAME);
+    }
+
+    Ok(())
+}
+
+/// Whether `path` is one of fyg's own TOML configs, rather than another tool's version file.
+fn is_fyg_config(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == FYG_CONFIG_NAME || name == OLD_FYG_CONFIG_NAME)
+}
+
+/// Join a relative path's components with forward slashes so the config works on every platform.
+/// Returns None for an empty path.
+fn to_portable_path(path: &Path) -> Option<PathBuf> {
+    let components: Vec<_> = path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect();
+    if components.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(components.join("/")))
+    }
+}
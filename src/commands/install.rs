@@ -1,32 +1,356 @@
 use std::{
     fs,
-    io::Write,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
 };
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use octocrab::models::repos::Release;
+use tracing::{debug, info, instrument, warn};
 
 use crate::{
-    commands::{get_binary_name, uninstall},
+    cache,
+    checksum::{parse_sums, sha512_bytes, sha512_file},
+    commands::{engine_paths, uninstall},
+    config::{ProjectFygConfig, UserConfig},
     dirs::FygDirs,
+    engine::{self, AssetKind, InstallReceipt, Variant},
+    lock::ProjectLock,
+    logging::status,
+    output::{CodedError, ErrorCode},
+    platform::{Platform, PLATFORM},
+    releases::{self, SHA512_SUMS_NAME},
     version::get_full_version,
 };
 
-pub async fn cmd(version: &str, force: bool) -> Result<()> {
-    let fyg_dirs = FygDirs::get();
+pub async fn cmd(version: Option<&str>, mono: bool, templates: bool, force: bool) -> Result<()> {
+    // With no version given, install whatever the project in the current directory needs.
+    let Some(version) = version else {
+        let project_fyg_dir = std::env::current_dir()?;
+        return sync(&project_fyg_dir, force).await;
+    };
+
+    let variant = Variant::from_mono(mono);
+    if !force && templates && engine::bin_path(&get_full_version(version), variant).is_file() {
+        // Just add the templates to the engine that's already installed.
+        status!("Version {} is already installed.", version);
+    } else {
+        install_engine(version, variant, force, None).await?;
+    }
+    if templates {
+        install_templates(version, variant, force, None).await?;
+    }
+
+    Ok(())
+}
 
+/// Install an engine or export templates from an archive that was downloaded some other way, e.g.
+/// for a machine without internet access. The archive is checked and added to the cache, then
+/// installed just like a cached download.
+pub async fn from_file(
+    archive_path: &Path,
+    version: Option<&str>,
+    mono: bool,
+    force: bool,
+    sha512: Option<&str>,
+    sums_path: Option<&Path>,
+) -> Result<()> {
+    let file_name = archive_path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let archive_file = fs::File::open(archive_path)
+        .with_context(|| format!("Could not open {}.", archive_path.display()))?;
+    let mut archive = zip::ZipArchive::new(archive_file)
+        .with_context(|| format!("{} is not a zip archive.", archive_path.display()))?;
+
+    // Explicit arguments win over what the archive looks like.
+    let identified = match identify_name(&file_name)? {
+        Some(identified) => Some(identified),
+        None => identify_contents(&mut archive)?,
+    };
+    let (full_version, kind, variant) = match (identified, version) {
+        (Some((identified_version, kind, variant)), version) => (
+            version.map_or(identified_version, get_full_version),
+            kind,
+            if mono { Variant::Mono } else { variant },
+        ),
+        (None, Some(version)) => {
+            let kind = if file_name.ends_with(".tpz") { AssetKind::Templates } else { AssetKind::Engine };
+            (get_full_version(version), kind, Variant::from_mono(mono))
+        }
+        (None, None) => bail!(
+            "Could not tell which version {} is. Pass it before --from-file, e.g. `fyg install 4.3 --from-file {}`.",
+            file_name,
+            archive_path.display(),
+        ),
+    };
+    let version = full_version.strip_suffix("-stable").unwrap_or(&full_version);
+    let description = match kind {
+        AssetKind::Engine => "engine",
+        AssetKind::Templates => "export templates",
+    };
+    check_contents(&mut archive, &full_version, kind, variant)
+        .with_context(|| format!("{} is not a {} {} archive of version {} for this platform.", file_name, variant, description, version))?;
+
+    // Don't add anything to the cache just to fail installing it.
+    if !force {
+        match kind {
+            AssetKind::Engine if engine::bin_path(&full_version, variant).is_file() => {
+                bail!("Version {} is already installed. Pass --force to re-install.", version);
+            }
+            AssetKind::Templates if engine::templates_dir(&full_version, variant).is_dir() => {
+                bail!("Export templates for version {} are already installed. Pass --force to re-install.", version);
+            }
+            _ => {}
+        }
+    }
+
+    // Check the archive against the given digest, or the sums published with the release.
+    let asset_name = match kind {
+        AssetKind::Engine => engine::get_archive_name(&full_version, variant),
+        AssetKind::Templates => engine::get_templates_name(&full_version, variant),
+    };
+    let expected_sha512 = match (sha512, sums_path) {
+        (Some(sha512), _) => Some(sha512.trim().to_ascii_lowercase()),
+        (None, Some(sums_path)) => {
+            let Some(sha512) = find_sum(sums_path, &asset_name, &file_name)? else {
+                bail!(CodedError::new(
+                    ErrorCode::NotFound,
+                    format!("{} has no sum for {}.", sums_path.display(), asset_name),
+                ));
+            };
+            Some(sha512)
+        }
+        (None, None) => {
+            // Releases publish their sums alongside the archives, so look for them there too.
+            let sums_path = archive_path.with_file_name(SHA512_SUMS_NAME);
+            if sums_path.is_file() {
+                find_sum(&sums_path, &asset_name, &file_name)?
+            } else {
+                None
+            }
+        }
+    };
+    let sha512 = sha512_file(archive_path)?;
+    match expected_sha512 {
+        Some(expected_sha512) if expected_sha512 != sha512 => {
+            bail!(CodedError::new(
+                ErrorCode::ChecksumMismatch,
+                format!("{} does not match the expected SHA512 sum.", archive_path.display()),
+            ));
+        }
+        Some(_) => status!("Verified SHA512 sum of {}.", file_name),
+        None => warn!(
+            "Could not verify {} since no SHA512 sum was found for it. Pass --sha512 or --sums to check it.",
+            file_name,
+        ),
+    }
+
+    // Add it to the cache under the name it would've been downloaded as.
+    let cache_dir = FygDirs::get().engines_cache()
+        .join(&full_version);
+    let cache_path = cache_dir.join(&asset_name);
+    let is_cached = cache_path.is_file()
+        && fs::canonicalize(&cache_path)? == fs::canonicalize(archive_path)?;
+    if !is_cached {
+        fs::create_dir_all(&cache_dir)?;
+        fs::copy(archive_path, &cache_path)
+            .with_context(|| format!("Could not copy {} to the cache.", archive_path.display()))?;
+        status!("Copied to: {}", cache_path.display());
+        for evicted in cache::collect_garbage(Some(&cache_path), false)? {
+            status!("Removed {} from the cache.", evicted.path.display());
+        }
+    }
+
+    match kind {
+        AssetKind::Engine => install_engine(version, variant, force, Some(&sha512)).await,
+        AssetKind::Templates => install_templates(version, variant, force, Some(&sha512)).await,
+    }
+}
+
+/// Work out what a release asset is from its name, e.g. "Godot_v4.3-stable_mono_linux_x86_64.zip".
+/// Engines for other platforms are an error rather than unknown.
+fn identify_name(name: &str) -> Result<Option<(String, AssetKind, Variant)>> {
+    let Some(full_version) = name.strip_prefix("Godot_v").and_then(|rest| rest.split('_').next()) else {
+        return Ok(None);
+    };
+    for variant in Variant::ALL {
+        if name == engine::get_templates_name(full_version, variant) {
+            return Ok(Some((full_version.to_string(), AssetKind::Templates, variant)));
+        }
+        for platform in Platform::ALL {
+            if name != engine::get_archive_name_for(platform, full_version, variant) {
+                continue;
+            }
+            if platform != PLATFORM {
+                bail!(
+                    "{} is for a different platform. This platform needs {}.",
+                    name,
+                    engine::get_archive_name(full_version, variant),
+                );
+            }
+            return Ok(Some((full_version.to_string(), AssetKind::Engine, variant)));
+        }
+    }
+    Ok(None)
+}
+
+/// Work out what an archive is from the files in it, for archives that have been renamed.
+fn identify_contents(archive: &mut zip::ZipArchive<fs::File>) -> Result<Option<(String, AssetKind, Variant)>> {
+    // Export templates say which version they're for, e.g. "4.3.stable.mono".
+    if let Some(templates_version) = read_templates_version(archive)? {
+        let (templates_version, variant) = match templates_version.strip_suffix(".mono") {
+            Some(templates_version) => (templates_version, Variant::Mono),
+            None => (templates_version.as_str(), Variant::Standard),
+        };
+        let Some((number, stage)) = templates_version.rsplit_once('.') else {
+            return Ok(None);
+        };
+        return Ok(Some((format!("{}-{}", number, stage), AssetKind::Templates, variant)));
+    }
+
+    // Engine archives contain a binary or directory named like the archive itself.
+    let top_level_names: Vec<String> = archive.file_names()
+        .filter_map(|name| name.split('/').next())
+        .map(str::to_string)
+        .collect();
+    for top_level_name in top_level_names {
+        if let Some(identified) = identify_name(&format!("{}.zip", top_level_name))? {
+            return Ok(Some(identified));
+        }
+    }
+    Ok(None)
+}
+
+fn read_templates_version(archive: &mut zip::ZipArchive<fs::File>) -> Result<Option<String>> {
+    let Ok(mut file) = archive.by_name("templates/version.txt") else {
+        return Ok(None);
+    };
+    let mut templates_version = String::new();
+    file.read_to_string(&mut templates_version)?;
+    Ok(Some(templates_version.trim().to_string()))
+}
+
+/// Make sure an archive has what it's about to be installed as.
+fn check_contents(archive: &mut zip::ZipArchive<fs::File>, full_version: &str, kind: AssetKind, variant: Variant) -> Result<()> {
+    match kind {
+        AssetKind::Engine => {
+            let version_dir = FygDirs::get().engines_data().join(full_version);
+            let bin_path = engine::bin_path(full_version, variant);
+            let bin_name = bin_path.strip_prefix(&version_dir)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if !archive.file_names().any(|name| name == bin_name) {
+                bail!("It doesn't contain {}.", bin_name);
+            }
+        }
+        AssetKind::Templates => {
+            let templates_dir = engine::templates_dir(full_version, variant);
+            let expected_version = templates_dir.file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            match read_templates_version(archive)? {
+                Some(templates_version) if templates_version == expected_version => {}
+                Some(templates_version) => bail!("It contains templates for {}.", templates_version),
+                None => bail!("It doesn't contain templates/version.txt."),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Look up an archive's digest in a sums file, under its release asset name or its own name.
+fn find_sum(sums_path: &Path, asset_name: &str, file_name: &str) -> Result<Option<String>> {
+    let sums = fs::read_to_string(sums_path)
+        .with_context(|| format!("Could not read {}.", sums_path.display()))?;
+    let mut sums = parse_sums(&sums);
+    Ok(sums.remove(asset_name).or_else(|| sums.remove(file_name)))
+}
+
+/// Install everything the project's fyg config at `project_fyg_dir` asks for.
+pub async fn sync(project_fyg_dir: &Path, force: bool) -> Result<()> {
+    let project_config = ProjectFygConfig::load(project_fyg_dir)?;
+    install_project(project_fyg_dir, &project_config, force).await
+}
+
+/// Install the engine, variant, and export templates a project needs, skipping anything that's
+/// already installed unless `force` is set. If the project has a fyg.lock, install exactly what
+/// it pins.
+pub async fn install_project(project_fyg_dir: &Path, project_config: &ProjectFygConfig, force: bool) -> Result<()> {
+    let engines_dir = project_config.engines_dir(project_fyg_dir);
+    install_project_to(project_fyg_dir, project_config, &engines_dir, project_config.templates, force).await
+}
+
+/// Like `install_project`, but install into `engines_dir`, and only install export templates if
+/// `templates` is set.
+pub async fn install_project_to(
+    project_fyg_dir: &Path,
+    project_config: &ProjectFygConfig,
+    engines_dir: &Path,
+    templates: bool,
+    force: bool,
+) -> Result<()> {
+    let lock = ProjectLock::load(project_fyg_dir)?;
+    if let Some(lock) = &lock {
+        lock.check(project_config)?;
+    }
+
+    let version = lock.as_ref()
+        .map_or(&project_config.version, |lock| &lock.tag);
+    let variant = project_config.variant();
     let full_version = get_full_version(version);
-    let bin_name = get_binary_name(&full_version);
-    let bin_path = fyg_dirs.engines_data()
-        .join(&full_version)
-        .join(&bin_name);
-    let zip_name = format!("{}.zip", &bin_name);
-    let zip_path = fyg_dirs.engines_cache()
-        .join(&full_version)
-        .join(&zip_name);
+    let locked_sha512 = |kind, platform| lock.as_ref()
+        .and_then(|lock| lock.find(kind, platform, variant))
+        .map(|asset| asset.sha512.as_str());
+
+    if force || !engine::bin_path_in(engines_dir, &full_version, variant).is_file() {
+        let expected_sha512 = locked_sha512(AssetKind::Engine, Some(PLATFORM));
+        install_engine_to(engines_dir, version, variant, force, expected_sha512).await?;
+    } else {
+        if let Some(lock) = &lock {
+            lock.verify_installed(engines_dir, variant)?;
+        }
+        status!("Version {} is already installed.", version);
+    }
+
+    if templates {
+        if force || !engine::templates_dir_in(engines_dir, &full_version, variant).is_dir() {
+            let expected_sha512 = locked_sha512(AssetKind::Templates, None);
+            install_templates_to(engines_dir, version, variant, force, expected_sha512).await?;
+        } else {
+            status!("Export templates for version {} are already installed.", version);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn install_engine(version: &str, variant: Variant, force: bool, expected_sha512: Option<&str>) -> Result<()> {
+    install_engine_to(FygDirs::get().engines_data(), version, variant, force, expected_sha512).await
+}
+
+/// Install an engine under `engines_dir`, e.g. a project's vendored engines.
+#[instrument(skip(expected_sha512))]
+pub async fn install_engine_to(
+    engines_dir: &Path,
+    version: &str,
+    variant: Variant,
+    force: bool,
+    expected_sha512: Option<&str>,
+) -> Result<()> {
+    let full_version = get_full_version(version);
+    let bin_path = engine::bin_path_in(engines_dir, &full_version, variant);
+    let engine_dir = engine::engine_dir_in(engines_dir, &full_version, variant);
 
     if force {
-        // Uninstall any existing version before installing.
-        uninstall(fyg_dirs.engines_data(), version)?;
+        // Uninstall any existing install of this variant before installing.
+        if !engine_paths(engines_dir, &full_version, variant)?.is_empty() {
+            uninstall(engines_dir, version, variant)?;
+        }
     } else {
         // Check if we already have this version installed.
         if bin_path.is_file() {
@@ -34,86 +358,250 @@ pub async fn cmd(version: &str, force: bool) -> Result<()> {
         }
     }
 
-    // Skip download if engine zip is cached.
-    if zip_path.is_file() {
-        // TODO: Check SHA512 sum of zip.
+    let zip_name = engine::get_archive_name(&full_version, variant);
+    let (zip_path, sha512) = fetch_asset(version, &zip_name, expected_sha512).await?;
+
+    // Unzip the engine to data dir under its version. Mono builds are zipped in their own
+    // directory, so they end up in a subdirectory.
+    let data_dir = engines_dir.join(&full_version);
+    extract_engine(&zip_path, &data_dir)?;
+
+    // By default, add an _sc_ file in the same directory to make Godot use Self-Contained Mode:
+    // https://docs.godotengine.org/en/latest/tutorials/io/data_paths.html#self-contained-mode
+    if UserConfig::get().self_contained() {
+        fs::File::create(engine_dir.join(engine::SELF_CONTAINED_NAME))?;
+    }
 
-        println!("Version {} is already downloaded. Extracting from cache.", version);
+    // Remember which archive this came from so it can be checked against a fyg.lock later.
+    InstallReceipt {
+        archive: zip_name,
+        sha512,
+    }.save(&engine_dir)?;
 
-        let zip_file = fs::File::open(&zip_path)?;
+    status!("Extracted to: {}", engine_dir.to_string_lossy());
 
-        let data_dir = fyg_dirs.engines_data()
-            .join(&full_version);
-        let mut archive = zip::ZipArchive::new(zip_file)?;
-        archive.extract(&data_dir)?;
+    Ok(())
+}
 
-        // By default, add an _sc_ file in the same directory to make Godot use Self-Contained Mode:
-        // https://docs.godotengine.org/en/latest/tutorials/io/data_paths.html#self-contained-mode
-        fs::File::create(data_dir.join("_sc_"))?;
+pub async fn install_templates(version: &str, variant: Variant, force: bool, expected_sha512: Option<&str>) -> Result<()> {
+    install_templates_to(FygDirs::get().engines_data(), version, variant, force, expected_sha512).await
+}
 
-        println!("Extracted to: {}", data_dir.to_string_lossy());
+/// Install export templates for an engine under `engines_dir`.
+#[instrument(skip(expected_sha512))]
+pub async fn install_templates_to(
+    engines_dir: &Path,
+    version: &str,
+    variant: Variant,
+    force: bool,
+    expected_sha512: Option<&str>,
+) -> Result<()> {
+    let full_version = get_full_version(version);
+    let templates_dir = engine::templates_dir_in(engines_dir, &full_version, variant);
 
-        return Ok(());
+    if templates_dir.is_dir() {
+        if !force {
+            bail!("Export templates for version {} are already installed. Pass --force to re-install.", version);
+        }
+        fs::remove_dir_all(&templates_dir)?;
     }
 
-    // Try to get the URL for this release.
-    let octocrab = octocrab::instance();
-    let maybe_release = octocrab.repos("godotengine", "godot")
-        .releases()
-        .get_by_tag(&full_version)
-        .await;
+    let tpz_name = engine::get_templates_name(&full_version, variant);
+    let (tpz_path, _) = fetch_asset(version, &tpz_name, expected_sha512).await?;
 
-    let Ok(release) = maybe_release else {
-        bail!("Version {} not found.", version);
-        // TODO: Get list of releases and print available releases.
-    };
+    extract_templates(&tpz_path, &templates_dir)?;
 
-    // Download package for this platform.
-    let maybe_url = release.assets.iter()
-        .find(|asset| asset.name == zip_name)
-        .map(|asset| &asset.browser_download_url);
-    let Some(package_url) = maybe_url else {
-        bail!(
-            "Version {} does not support your platform.\nTuxFamily may have a build available: https://downloads.tuxfamily.org/godotengine/{}/",
-            version,
-            version,
-        );
-    };
+    status!("Export templates extracted to: {}", templates_dir.to_string_lossy());
 
-    println!("Package URL: {}", package_url);
+    Ok(())
+}
+
+#[instrument]
+fn extract_engine(zip_path: &Path, data_dir: &Path) -> Result<()> {
+    let start = Instant::now();
+    let zip_file = fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(zip_file)?;
+    archive.extract(data_dir)?;
+    info!(files = archive.len(), elapsed_ms = start.elapsed().as_millis() as u64, "Extracted");
+    Ok(())
+}
+
+#[instrument]
+fn extract_templates(tpz_path: &Path, templates_dir: &Path) -> Result<()> {
+    let start = Instant::now();
+
+    // The .tpz is a zip with all the templates in a templates/ directory. Extract them to where
+    // a self-contained editor looks for them.
+    let tpz_file = fs::File::open(tpz_path)?;
+    let mut archive = zip::ZipArchive::new(tpz_file)?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let Some(path) = file.enclosed_name() else {
+            continue;
+        };
+        let Ok(relative_path) = path.strip_prefix("templates") else {
+            continue;
+        };
+        if file.is_dir() || relative_path.as_os_str().is_empty() {
+            continue;
+        }
+        let out_path = templates_dir.join(relative_path);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out_file = fs::File::create(&out_path)
+            .with_context(|| format!("Could not create {}.", out_path.display()))?;
+        io::copy(&mut file, &mut out_file)?;
+    }
+    info!(elapsed_ms = start.elapsed().as_millis() as u64, "Extracted");
+    Ok(())
+}
 
-    // Download the file.
-    let response = reqwest::get(package_url.as_str())
-        .await?;
-    let content = response.bytes()
-        .await?;
+/// Get the path to a version's release asset in the engines cache, downloading it first if it's
+/// not cached yet. Also returns the asset's SHA512 digest, which must match `expected_sha512` if
+/// given.
+#[instrument(skip(expected_sha512))]
+pub async fn fetch_asset(version: &str, asset_name: &str, expected_sha512: Option<&str>) -> Result<(PathBuf, String)> {
+    let fyg_dirs = FygDirs::get();
 
-    // Copy content to cache directory for versions.
+    let full_version = get_full_version(version);
     let cache_dir = fyg_dirs.engines_cache()
         .join(&full_version);
+    let download_path = cache_dir.join(asset_name);
+
+    // Skip download if the asset is cached.
+    if download_path.is_file() {
+        debug!(path = %download_path.display(), "Using cached asset");
+        status!("{} is already downloaded.", asset_name);
+
+        // Cached archives were checked against the release's sums when downloaded, but a lock
+        // may pin a different build.
+        let sha512 = sha512_file(&download_path)?;
+        if let Some(expected_sha512) = expected_sha512 {
+            if sha512 != expected_sha512 {
+                bail!(CodedError::new(
+                    ErrorCode::ChecksumMismatch,
+                    format!(
+                        "Cached {} does not match the digest in fyg.lock. Remove it with `fyg cache rm {}` to download it again.",
+                        download_path.display(),
+                        full_version.strip_suffix("-stable").unwrap_or(&full_version),
+                    ),
+                ));
+            }
+        }
+        cache::touch(&download_path)?;
+        return Ok((download_path, sha512));
+    }
+
+    // Try the user's mirrors first, then GitHub.
+    let mut release = None;
+    let content = if let Some((package_url, content)) = releases::download_from_mirrors(&full_version, asset_name).await {
+        status!("Package URL: {}", package_url);
+        content
+    } else {
+        // Try to get the URL for this release.
+        let Some(github_release) = releases::get_release(&full_version).await else {
+            bail!(CodedError::new(ErrorCode::NotFound, format!("Version {} not found.", version)));
+            // TODO: Get list of releases and print available releases.
+        };
+
+        // Download package for this platform.
+        let maybe_url = github_release.assets.iter()
+            .find(|asset| asset.name == asset_name)
+            .map(|asset| &asset.browser_download_url);
+        let Some(package_url) = maybe_url else {
+            bail!(CodedError::new(
+                ErrorCode::NotFound,
+                format!(
+                    "Version {} has no {} download for your platform.\nTuxFamily may have a build available: https://downloads.tuxfamily.org/godotengine/{}/",
+                    version,
+                    asset_name,
+                    version,
+                ),
+            ));
+        };
+
+        status!("Package URL: {}", package_url);
+
+        // Download the file.
+        let content = releases::download_with_progress(package_url.as_str()).await?;
+        release = Some(github_release);
+        content
+    };
+
+    // Check the download against the lock, or the sums published with the release.
+    let sha512 = sha512_bytes(&content);
+    verify_download(&full_version, asset_name, &sha512, expected_sha512, release.as_ref()).await?;
+
+    // Copy content to cache directory for versions. Write it under another name first, so an
+    // interrupted write doesn't leave a truncated asset that looks cached.
     fs::create_dir_all(&cache_dir)?;
-    let download_path = cache_dir.join(&zip_name);
+    let partial_path = cache_dir.join(format!("{}.part", asset_name));
     {
-        let mut file = fs::File::create(&download_path)?;
+        let mut file = fs::File::create(&partial_path)?;
         file.write_all(&content)?;
     }
+    fs::rename(&partial_path, &download_path)?;
 
-    // TODO: Check SHA512 sum of zip.
+    status!("Downloaded to: {}", download_path.to_string_lossy());
 
-    println!("Downloaded to: {}", download_path.to_string_lossy());
-
-    // Unzip downloaded file to data dir under its version.
-    let data_dir = fyg_dirs.engines_data()
-        .join(&full_version);
-    let seekable_content = std::io::Cursor::new(content.as_ref());
-    let mut archive = zip::ZipArchive::new(seekable_content)?;
-    archive.extract(&data_dir)?;
+    // Make room for the new download if the cache has outgrown the user's limits.
+    for evicted in cache::collect_garbage(Some(&download_path), false)? {
+        status!("Removed {} from the cache.", evicted.path.display());
+    }
 
-    // By default, add an _sc_ file in the same directory to make Godot use Self-Contained Mode:
-    // https://docs.godotengine.org/en/latest/tutorials/io/data_paths.html#self-contained-mode
-    fs::File::create(data_dir.join("_sc_"))?;
+    Ok((download_path, sha512))
+}
 
-    println!("Extracted to: {}", data_dir.to_string_lossy());
+/// Check a downloaded asset's digest against the one in the project's lock if there is one, or else
+/// the release's published sums.
+#[instrument(skip(sha512, expected_sha512, release))]
+async fn verify_download(
+    full_version: &str,
+    asset_name: &str,
+    sha512: &str,
+    expected_sha512: Option<&str>,
+    release: Option<&Release>,
+) -> Result<()> {
+    if let Some(expected_sha512) = expected_sha512 {
+        if sha512 != expected_sha512 {
+            bail!(CodedError::new(
+                ErrorCode::ChecksumMismatch,
+                format!(
+                    "Downloaded {} does not match the digest in fyg.lock. The release may have been re-tagged; run `fyg lock --update` if this is expected.",
+                    asset_name,
+                ),
+            ));
+        }
+        debug!("Matches the digest in fyg.lock");
+        return Ok(());
+    }
 
+    let mut published_sums = match release {
+        Some(release) => releases::get_sha512_sums(release).await?,
+        None => releases::get_mirror_sha512_sums(full_version).await,
+    };
+    // Mirrors may not copy the sums, so check against the ones published on GitHub instead.
+    if release.is_none() && !published_sums.contains_key(asset_name) {
+        if let Some(github_release) = releases::get_release(full_version).await {
+            match releases::get_sha512_sums(&github_release).await {
+                Ok(github_sums) => published_sums = github_sums,
+                Err(err) => debug!(error = %err, "Could not download the release's SHA512 sums"),
+            }
+        }
+    }
+    match published_sums.get(asset_name) {
+        Some(published_sha512) if sha512 != published_sha512 => {
+            bail!(CodedError::new(
+                ErrorCode::ChecksumMismatch,
+                format!(
+                    "Downloaded {} does not match its published SHA512 sum.",
+                    asset_name,
+                ),
+            ));
+        }
+        Some(_) => debug!("Matches the published SHA512 sum"),
+        None => warn!("Could not verify {}, since no SHA512 sum is published for it.", asset_name),
+    }
     Ok(())
 }
@@ -3,28 +3,34 @@ use std::process::{Command, Stdio};
 use anyhow::{bail, Result};
 
 use crate::{
-    commands::get_binary_name,
-    dirs::FygDirs,
+    config::UserConfig,
+    engine::{self, Variant},
+    output::{CodedError, ErrorCode},
     version::get_full_version,
 };
 
-pub fn cmd(version: &str) -> Result<()> {
-    let fyg_dirs = FygDirs::get();
+pub fn cmd(version: Option<&str>, variant: Variant) -> Result<()> {
+    let user_config = UserConfig::get();
+    let Some(version) = version.or(user_config.default_version.as_deref()) else {
+        bail!("No version given and no default_version set. Set one with `fyg config set default_version <VERSION>`.");
+    };
 
     // Try to launch the specified version.
     let full_version = get_full_version(version);
-    let bin_name = get_binary_name(&full_version);
-    let bin_path = fyg_dirs.engines_data()
-        .join(&full_version)
-        .join(bin_name);
+    let bin_path = engine::bin_path(&full_version, variant);
 
     if !bin_path.is_file() {
-        bail!("Version {} is not installed.", version);
+        let message = match variant {
+            Variant::Standard => format!("Version {} is not installed.", version),
+            Variant::Mono => format!("The Mono build of version {} is not installed.", version),
+        };
+        bail!(CodedError::new(ErrorCode::NotInstalled, message));
     }
 
     println!("Running: {}", bin_path.to_string_lossy());
     Command::new(&bin_path)
         .arg("--project-manager")
+        .args(&user_config.launch_args)
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -1,94 +1,280 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use octocrab::models::repos::Release;
 use owo_colors::OwoColorize;
+use regex::Regex;
+use serde::Serialize;
 
 use crate::{
-    commands::get_binary_name,
+    commands::{dir_size, engine_paths},
     dirs::FygDirs,
-    version::get_full_version,
+    engine::{self, Variant},
+    output::{self, Format},
+    releases,
+    version::{get_full_version, Stage, Version},
 };
 
+/// Which versions to list.
+pub struct Filter {
+    prefix: Option<String>,
+    regex: Option<Regex>,
+    prereleases: bool,
+    stable_only: bool,
+}
+
+impl Filter {
+    pub fn new(prefix: Option<&str>, regex: Option<&str>, prereleases: bool, stable_only: bool) -> Result<Filter> {
+        let regex = regex.map(|regex| {
+            Regex::new(regex)
+                .with_context(|| format!("Invalid regular expression \"{}\".", regex))
+        }).transpose()?;
+        Ok(Filter {
+            prefix: prefix.map(|prefix| prefix.trim().to_string()),
+            regex,
+            prereleases,
+            stable_only,
+        })
+    }
+
+    /// Check the prefix and regex against a version and its full tag.
+    fn matches(&self, version: &str, tag: &str) -> bool {
+        if let Some(prefix) = &self.prefix {
+            // Only match whole parts of the version, so "4.2" doesn't match "4.20".
+            let matches_prefix = version == prefix ||
+                version.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with(['.', '-']));
+            if !matches_prefix {
+                return false;
+            }
+        }
+        match &self.regex {
+            Some(regex) => regex.is_match(tag),
+            None => true,
+        }
+    }
+}
+
+/// An installed engine, as printed by `list --format json`.
+#[derive(Serialize)]
+struct InstalledRecord {
+    version: String,
+    tag: String,
+    variant: Variant,
+    /// Path to the engine's binary.
+    path: PathBuf,
+    /// Size in bytes of the engine's files on disk, including its export templates.
+    size: u64,
+    /// Whether the engine's archive is still in the cache.
+    cached: bool,
+}
+
+/// A release on GitHub, as printed by `list --available --format json`.
+#[derive(Serialize)]
+struct AvailableRecord {
+    version: String,
+    tag: String,
+    prerelease: bool,
+    /// Variants the release has a download of for this platform.
+    variants: Vec<Variant>,
+    installed: Vec<Variant>,
+    cached: Vec<Variant>,
+    /// Size in bytes of each variant's download for this platform.
+    download_sizes: BTreeMap<Variant, u64>,
+}
+
 #[must_use]
-fn is_installed(version: &str, fyg_dirs: &FygDirs) -> bool {
-    let full_version = get_full_version(version);
-    let bin_name = get_binary_name(&full_version);
-    let bin_path = fyg_dirs.engines_data()
-        .join(&full_version)
-        .join(&bin_name);
-    bin_path.is_file()
+pub fn is_cached(full_version: &str, variant: Variant) -> bool {
+    FygDirs::get().engines_cache()
+        .join(full_version)
+        .join(engine::get_archive_name(full_version, variant))
+        .is_file()
 }
 
-pub async fn cmd(available: bool) -> Result<()> {
-    let fyg_dirs = FygDirs::get();
+/// Whether the release has a download of the variant for this platform.
+#[must_use]
+pub fn has_archive(release: &Release, variant: Variant) -> bool {
+    let archive_name = engine::get_archive_name(&get_full_version(&release.tag_name), variant);
+    release.assets.iter().any(|asset| asset.name == archive_name)
+}
 
-    if !available {
-        if !fyg_dirs.engines_data().is_dir() {
-            // Engines directory doesn't exist, so no engines installed.
-            return Ok(());
+#[must_use]
+fn is_prerelease(release: &Release) -> bool {
+    release.prerelease ||
+        Version::parse(&release.tag_name).is_some_and(|version| version.stage != Stage::Stable)
+}
+
+fn available_record(release: &Release) -> AvailableRecord {
+    let full_version = get_full_version(&release.tag_name);
+    let version = release.tag_name.strip_suffix("-stable")
+        .unwrap_or(&release.tag_name);
+    AvailableRecord {
+        version: version.to_string(),
+        tag: release.tag_name.clone(),
+        prerelease: is_prerelease(release),
+        variants: Variant::ALL.into_iter()
+            .filter(|variant| has_archive(release, *variant))
+            .collect(),
+        installed: Variant::ALL.into_iter()
+            .filter(|variant| engine::bin_path(&full_version, *variant).is_file())
+            .collect(),
+        cached: Variant::ALL.into_iter()
+            .filter(|variant| is_cached(&full_version, *variant))
+            .collect(),
+        download_sizes: Variant::ALL.into_iter()
+            .filter_map(|variant| {
+                let archive_name = engine::get_archive_name(&full_version, variant);
+                release.assets.iter()
+                    .find(|asset| asset.name == archive_name)
+                    .map(|asset| (variant, asset.size.max(0) as u64))
+            })
+            .collect(),
+    }
+}
+
+/// Size in bytes of everything installed for one variant of an engine.
+fn installed_size(full_version: &str, variant: Variant) -> Result<u64> {
+    let mut size = 0;
+    for path in engine_paths(FygDirs::get().engines_data(), full_version, variant)? {
+        size += if path.is_dir() { dir_size(&path)? } else { fs::metadata(&path)?.len() };
+    }
+    Ok(size)
+}
+
+/// The newest stable version among the releases.
+fn latest_stable(releases: &[Release]) -> Option<Version> {
+    releases.iter()
+        .filter(|release| !is_prerelease(release))
+        .filter_map(|release| Version::parse(&release.tag_name))
+        .max()
+}
+
+/// Sort releases newest first, with tags that aren't versions last, and keep those that pass the
+/// filter.
+pub fn filter_releases(releases: Vec<Release>, filter: &Filter) -> Vec<Release> {
+    let latest_stable = latest_stable(&releases);
+    filter_releases_against(releases, filter, latest_stable.as_ref())
+}
+
+/// Like `filter_releases`, but decide which prereleases are for an upcoming version by comparing
+/// them with `latest_stable`.
+fn filter_releases_against(mut releases: Vec<Release>, filter: &Filter, latest_stable: Option<&Version>) -> Vec<Release> {
+    releases.sort_by_key(|release| Reverse(Version::parse(&release.tag_name)));
+
+    // Prereleases are only interesting if they're for an upcoming version, unless asked for.
+    let show_prerelease = |release: &Release| {
+        if filter.stable_only {
+            return false;
         }
+        filter.prereleases || match (latest_stable, Version::parse(&release.tag_name)) {
+            (Some(latest_stable), Some(version)) => &version > latest_stable,
+            _ => true,
+        }
+    };
+
+    releases.retain(|release| {
+        let release_version = release.tag_name.strip_suffix("-stable")
+            .unwrap_or(&release.tag_name);
+        filter.matches(release_version, &release.tag_name) &&
+            (!is_prerelease(release) || show_prerelease(release))
+    });
+    releases
+}
 
-        // Start by finding the installed versions.
-        let read_dir = fs::read_dir(fyg_dirs.engines_data())?;
-        // By default, list just the installed versions.
-        for entry in read_dir {
-            let entry = entry?;
-            let version_path = entry.path();
-            if version_path.is_dir() {
-                let file_name = entry.file_name();
-                let full_version = file_name.to_string_lossy();
-                let bin_name = get_binary_name(&full_version);
-                let bin_path = fyg_dirs.engines_data()
-                    .join(full_version.as_ref())
-                    .join(bin_name);
-                // TODO: Also check that it's executable?
-                if bin_path.is_file() {
-                    let version = full_version.strip_suffix("-stable")
-                        .unwrap_or(&full_version);
-                    println!("{}", &version);
-                }
+pub async fn cmd(available: bool, filter: &Filter, variant: Variant, format: Format) -> Result<()> {
+    if !available {
+        // By default, list just the installed versions, newest first.
+        let mut installed_engines = engine::installed_engines()?;
+        installed_engines.sort_by_key(|(full_version, _)| Reverse(Version::parse(full_version)));
+
+        let mut records = Vec::new();
+        for (full_version, installed_variant) in installed_engines {
+            let version = full_version.strip_suffix("-stable")
+                .unwrap_or(&full_version);
+            if !filter.matches(version, &full_version) {
+                continue;
+            }
+            if variant == Variant::Mono && installed_variant != Variant::Mono {
+                continue;
+            }
+            if format != Format::Text {
+                records.push(InstalledRecord {
+                    version: version.to_string(),
+                    tag: full_version.clone(),
+                    variant: installed_variant,
+                    path: engine::bin_path(&full_version, installed_variant),
+                    size: installed_size(&full_version, installed_variant)?,
+                    cached: is_cached(&full_version, installed_variant),
+                });
+                continue;
+            }
+            match installed_variant {
+                Variant::Standard => println!("{}", &version),
+                Variant::Mono => println!("{} (mono)", &version),
             }
         }
 
+        return output::print_records(format, &records);
+    }
+
+    // Print each page as it comes in rather than waiting for every page, so releases are only
+    // sorted within their page. GitHub lists the newest first, so the latest stable version to
+    // judge prereleases by is almost always on the first page.
+    if format == Format::Ndjson {
+        let mut latest_stable_so_far = None;
+        releases::list_releases_by_page(|page| {
+            latest_stable_so_far = latest_stable_so_far.take().max(latest_stable(page));
+            for release in filter_releases_against(page.to_vec(), filter, latest_stable_so_far.as_ref()) {
+                output::print_json_line(&available_record(&release))?;
+            }
+            Ok(())
+        }).await?;
         return Ok(());
     }
 
     // Query GitHub for list of Godot Releases.
-    let octocrab = octocrab::instance();
-    let mut page = octocrab.repos("godotengine", "godot")
-        .releases()
-        .list()
-        .per_page(100)
-        .send()
-        .await?;
-
-    // List release versions.
-    // TODO: Filter out/mark ones that don't support this platform.
-    // TODO: Add option for ones with mono versions.
-    // TODO: Sort by version number.
-    loop {
-        // List versions on this page.
-        for release in &page.items {
-            let release_version = release.tag_name.strip_suffix("-stable")
-                .unwrap_or(&release.tag_name);
-            if is_installed(release_version, &fyg_dirs) {
-                let installed = format!("{} (installed)", release_version);
-                println!("{}", installed.bold());
-            } else {
-                println!("{}", release_version);
-            }
+    let releases = filter_releases(releases::list_releases().await?, filter);
+
+    let mut records = Vec::new();
+    for release in &releases {
+        let full_version = get_full_version(&release.tag_name);
+        let release_version = release.tag_name.strip_suffix("-stable")
+            .unwrap_or(&release.tag_name);
+
+        if format != Format::Text {
+            records.push(available_record(release));
+            continue;
+        }
+
+        let mut markers = Vec::new();
+        let installed = engine::bin_path(&full_version, variant).is_file();
+        if installed {
+            markers.push("installed");
+        }
+        if is_cached(&full_version, variant) {
+            markers.push("cached");
+        }
+        let unavailable = !has_archive(release, variant);
+        if unavailable {
+            markers.push("unavailable");
         }
 
-        // Try to get the next page, if any.
-        page = match octocrab
-            .get_page::<Release>(&page.next)
-            .await?
-        {
-            Some(next_page) => next_page,
-            None => break,
+        let line = if markers.is_empty() {
+            release_version.to_string()
+        } else {
+            format!("{} ({})", release_version, markers.join(", "))
+        };
+        if installed {
+            println!("{}", line.bold());
+        } else if unavailable {
+            println!("{}", line.dimmed());
+        } else {
+            println!("{}", line);
         }
     }
 
-    Ok(())
+    output::print_records(format, &records)
 }
@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::{
+    commands::install::fetch_asset,
+    config::ProjectFygConfig,
+    engine::{self, AssetKind},
+    lock::{LockedAsset, ProjectLock},
+    output::{CodedError, ErrorCode},
+    platform::{PLATFORM, Platform},
+    releases,
+    version::get_full_version,
+};
+
+pub async fn cmd(project_fyg_dir: &Path, update: bool) -> Result<()> {
+    let project_config = ProjectFygConfig::load(project_fyg_dir)?;
+
+    // Keep an existing lock that still matches the config, unless asked to update.
+    if !update {
+        if let Some(lock) = ProjectLock::load(project_fyg_dir)? {
+            if lock.check(&project_config).is_ok() {
+                println!("{} is up to date.", ProjectLock::path(project_fyg_dir).display());
+                return Ok(());
+            }
+        }
+    }
+
+    let lock = resolve(&project_config).await?;
+    lock.save(project_fyg_dir)?;
+
+    println!(
+        "Locked version {} to {} in {}.",
+        &project_config.version,
+        &lock.tag,
+        ProjectLock::path(project_fyg_dir).display(),
+    );
+
+    Ok(())
+}
+
+/// Resolve a project's version to an exact release and record the digest of every archive the
+/// project could be installed from.
+pub async fn resolve(project_config: &ProjectFygConfig) -> Result<ProjectLock> {
+    let full_version = get_full_version(&project_config.version);
+    let Some(release) = releases::get_release(&full_version).await else {
+        bail!(CodedError::new(
+            ErrorCode::NotFound,
+            format!(
+                "Version {} not found.",
+                &project_config.version,
+            ),
+        ));
+    };
+    let sums = releases::get_sha512_sums(&release).await?;
+
+    // Lock the engine for every platform so the whole team gets the same build.
+    let variant = project_config.variant();
+    let mut wanted: Vec<_> = Platform::ALL.into_iter()
+        .map(|platform| (
+            AssetKind::Engine,
+            Some(platform),
+            engine::get_archive_name_for(platform, &full_version, variant),
+        ))
+        .collect();
+    if project_config.templates {
+        wanted.push((AssetKind::Templates, None, engine::get_templates_name(&full_version, variant)));
+    }
+
+    let mut assets = Vec::new();
+    for (kind, platform, name) in wanted {
+        let Some(asset) = release.assets.iter().find(|asset| asset.name == name) else {
+            // Not every release has builds for every platform.
+            if platform.is_none() || platform == Some(PLATFORM) {
+                bail!(CodedError::new(
+                    ErrorCode::NotFound,
+                    format!(
+                        "Version {} has no {} download.",
+                        &project_config.version,
+                        name,
+                    ),
+                ));
+            }
+            continue;
+        };
+
+        let sha512 = match sums.get(&name) {
+            Some(sha512) => sha512.clone(),
+            // Older releases don't publish sums, so hash the archive ourselves if we'll use it.
+            None if platform.is_none() || platform == Some(PLATFORM) => {
+                fetch_asset(&full_version, &name, None).await?.1
+            }
+            None => {
+                println!("Skipping {}: no published SHA512 sum.", name);
+                continue;
+            }
+        };
+
+        assets.push(LockedAsset {
+            name,
+            kind,
+            platform,
+            variant,
+            sha512,
+            source: asset.browser_download_url.to_string(),
+        });
+    }
+
+    Ok(ProjectLock {
+        version: project_config.version.clone(),
+        tag: release.tag_name,
+        assets,
+    })
+}
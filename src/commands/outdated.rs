@@ -0,0 +1,123 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+use crate::{
+    config::ProjectFygConfig,
+    engine,
+    output::{self, Format},
+    releases,
+    version::{Stage, Version},
+};
+
+/// Exit status for `outdated --check` when the project's version has a newer patch release. Errors
+/// exit with 1, so CI can tell the two apart.
+const OUTDATED_EXIT_CODE: u8 = 2;
+
+/// Where a version being checked comes from.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Source {
+    Project,
+    Installed,
+}
+
+/// A version and the newer releases available for it, as printed by `outdated --format json`.
+#[derive(Serialize)]
+struct OutdatedRecord {
+    source: Source,
+    version: String,
+    /// The newest stable release in the same minor series, if it's newer.
+    latest_patch: Option<String>,
+    /// The newest stable release overall, if it's newer.
+    latest: Option<String>,
+    /// Whether there's a newer patch release to upgrade to.
+    outdated: bool,
+}
+
+impl OutdatedRecord {
+    fn new(source: Source, version: &Version, stable_versions: &[Version]) -> Self {
+        let latest_patch = stable_versions.iter()
+            .filter(|stable| stable.minor_series() == version.minor_series())
+            .max()
+            .filter(|latest_patch| *latest_patch > version);
+        let latest = stable_versions.iter()
+            .max()
+            .filter(|latest| *latest > version);
+        Self {
+            source,
+            version: version.to_string(),
+            latest_patch: latest_patch.map(Version::to_string),
+            latest: latest.map(Version::to_string),
+            outdated: latest_patch.is_some(),
+        }
+    }
+}
+
+pub async fn cmd(project_fyg_dir: &Path, check: bool, format: Format) -> Result<ExitCode> {
+    // Only check the project if there is one, unless the caller relies on it.
+    let project_version = if check || ProjectFygConfig::find_path(project_fyg_dir).is_some() {
+        let project_config = ProjectFygConfig::load(project_fyg_dir)?;
+        Version::parse(&project_config.version)
+    } else {
+        None
+    };
+
+    let mut installed_versions: Vec<_> = engine::installed_engines()?
+        .into_iter()
+        .filter_map(|(full_version, _)| Version::parse(&full_version))
+        .collect();
+    installed_versions.sort_by(|a, b| b.cmp(a));
+    installed_versions.dedup();
+
+    let stable_versions: Vec<_> = releases::list_releases().await?
+        .into_iter()
+        .filter(|release| !release.prerelease)
+        .filter_map(|release| Version::parse(&release.tag_name))
+        .filter(|version| version.stage == Stage::Stable)
+        .collect();
+
+    let mut records = Vec::new();
+    if let Some(project_version) = &project_version {
+        records.push(OutdatedRecord::new(Source::Project, project_version, &stable_versions));
+    }
+    for installed_version in &installed_versions {
+        records.push(OutdatedRecord::new(Source::Installed, installed_version, &stable_versions));
+    }
+
+    if format == Format::Text {
+        for record in &records {
+            let source = match record.source {
+                Source::Project => "project",
+                Source::Installed => "installed",
+            };
+            let mut line = format!("{} ({}): ", record.version, source);
+            match (&record.latest_patch, &record.latest) {
+                (Some(latest_patch), Some(latest)) if latest_patch != latest => {
+                    line.push_str(&format!("{} available, newest is {}", latest_patch, latest));
+                }
+                (Some(latest_patch), _) => line.push_str(&format!("{} available", latest_patch)),
+                (None, Some(latest)) => line.push_str(&format!("up to date, newest is {}", latest)),
+                (None, None) => line.push_str("up to date"),
+            }
+            if record.outdated {
+                println!("{}", line.yellow());
+            } else {
+                println!("{}", line);
+            }
+        }
+    } else {
+        output::print_records(format, &records)?;
+    }
+
+    let project_outdated = records.iter()
+        .any(|record| record.source == Source::Project && record.outdated);
+    if check && project_outdated {
+        return Ok(ExitCode::from(OUTDATED_EXIT_CODE));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
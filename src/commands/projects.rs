@@ -0,0 +1,173 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{self, Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+use crate::{
+    cli::ProjectsCommand,
+    config::ProjectFygConfig,
+    engine::{self, Variant},
+    output::{self, Format},
+    project::{ProjectGodot, PROJECT_GODOT_NAME},
+    registry::{KnownProject, ProjectRegistry},
+};
+
+/// Godot 4's list of projects in its editor data, with a section per project path.
+static GODOT_PROJECTS_CFG_NAME: &str = "projects.cfg";
+
+/// A known project, as printed by `projects --format json`.
+#[derive(Serialize)]
+struct ProjectRecord<'a> {
+    path: &'a Path,
+    /// The directory containing project.godot.
+    godot_dir: &'a Path,
+    name: Option<&'a str>,
+    version: &'a str,
+    variant: Variant,
+    last_opened: Option<DateTime<Utc>>,
+    /// Whether the engine the project is pinned to is installed.
+    installed: bool,
+}
+
+pub fn cmd(projects_command: &Option<ProjectsCommand>, format: Format) -> Result<()> {
+    match projects_command {
+        Some(ProjectsCommand::List) | None => list(format),
+        Some(ProjectsCommand::Forget { missing, paths }) => forget(paths, *missing),
+        Some(ProjectsCommand::Import) => import(),
+    }
+}
+
+fn list(format: Format) -> Result<()> {
+    let mut registry = ProjectRegistry::load()?;
+
+    // Show the most recently opened projects first.
+    registry.projects.sort_by_key(|project| Reverse(project.last_opened));
+
+    if format != Format::Text {
+        let records: Vec<_> = registry.projects.iter()
+            .map(|project| ProjectRecord {
+                path: &project.path,
+                godot_dir: project.godot_dir(),
+                name: project.name.as_deref(),
+                version: &project.version,
+                variant: project.variant(),
+                last_opened: project.last_opened,
+                installed: project.is_engine_installed(),
+            })
+            .collect();
+        return output::print_records(format, &records);
+    }
+
+    for project in &registry.projects {
+        let name = project.name.as_deref()
+            .unwrap_or("(unnamed)");
+        let version = match project.variant() {
+            Variant::Standard => project.version.clone(),
+            Variant::Mono => format!("{} mono", &project.version),
+        };
+        let mut line = format!("{} ({}): {}", name, version, project.path.display());
+        if let Some(last_opened) = project.last_opened {
+            let last_opened = last_opened.with_timezone(&Local);
+            line.push_str(&format!(", last opened {}", last_opened.format("%Y-%m-%d %H:%M")));
+        }
+        if project.is_engine_installed() {
+            println!("{}", line);
+        } else {
+            println!("{} {}", line, "(engine not installed)".red());
+        }
+    }
+
+    Ok(())
+}
+
+fn forget(paths: &[PathBuf], missing: bool) -> Result<()> {
+    let mut registry = ProjectRegistry::load()?;
+
+    for path in paths {
+        // Projects are recorded by canonical path, but forgotten ones may not exist anymore.
+        let path = path.canonicalize()
+            .or_else(|_| path::absolute(path))?;
+        if registry.forget(&path) {
+            println!("Forgot {}", path.display());
+        } else {
+            println!("Project {} is not known", path.display());
+        }
+    }
+
+    if missing {
+        registry.projects.retain(|project| {
+            let exists = project.path.is_dir();
+            if !exists {
+                println!("Forgot {}", project.path.display());
+            }
+            exists
+        });
+    }
+
+    registry.save()
+}
+
+/// Add the projects from each installed engine's own project list.
+fn import() -> Result<()> {
+    let mut registry = ProjectRegistry::load()?;
+    let mut imported = 0;
+
+    for (full_version, variant) in engine::installed_engines()? {
+        let projects_cfg_path = engine::engine_dir(&full_version, variant)
+            .join("editor_data")
+            .join(GODOT_PROJECTS_CFG_NAME);
+        if !projects_cfg_path.is_file() {
+            continue;
+        }
+
+        let projects_cfg = fs::read_to_string(&projects_cfg_path)?;
+        let project_paths = projects_cfg.lines()
+            .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+            .map(|section| PathBuf::from(section.trim_matches('"')));
+        for project_path in project_paths {
+            let project_godot_path = project_path.join(PROJECT_GODOT_NAME);
+            if !project_godot_path.is_file() {
+                continue;
+            }
+            let project_path = project_path.canonicalize()?;
+            if registry.find(&project_path).is_some() {
+                continue;
+            }
+
+            // Prefer the version the project pins, falling back to the engine that knew about it.
+            let (version, mono) = match ProjectFygConfig::load(&project_path) {
+                Ok(project_config) => (project_config.version, project_config.mono),
+                Err(_) => {
+                    let version = full_version.strip_suffix("-stable")
+                        .unwrap_or(&full_version);
+                    (version.to_string(), variant == Variant::Mono)
+                }
+            };
+            let name = ProjectGodot::load(&project_godot_path)
+                .ok()
+                .and_then(|project_godot| project_godot.name);
+
+            println!("Imported {}", project_path.display());
+            registry.insert(KnownProject {
+                path: project_path,
+                godot_dir: None,
+                name,
+                version,
+                mono,
+                last_opened: None,
+            });
+            imported += 1;
+        }
+    }
+
+    registry.save()?;
+    println!("Imported {} projects.", imported);
+
+    Ok(())
+}
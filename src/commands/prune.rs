@@ -0,0 +1,101 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+use anyhow::Result;
+use tracing::warn;
+
+use crate::{
+    commands::{confirm_removal, engine_paths, uninstall},
+    config::UserConfig,
+    dirs::FygDirs,
+    engine::{self, Variant},
+    registry::ProjectRegistry,
+    version::Version,
+};
+
+pub fn cmd(dry_run: bool, cache: bool, include_newest: bool, yes: bool) -> Result<()> {
+    let fyg_dirs = FygDirs::get();
+
+    // Find the versions that known projects and the user's default still use. Compare them parsed,
+    // since e.g. "4.2.0" and "4.2-stable" are the same engine.
+    let registry = ProjectRegistry::load()?;
+    let mut used_versions: HashSet<Version> = registry.projects.iter()
+        .filter_map(|project| Version::parse(&project.version))
+        .collect();
+    if let Some(default_version) = UserConfig::get().default_version.as_deref().and_then(Version::parse) {
+        used_versions.insert(default_version);
+    }
+
+    // Variants of the same version are installed in the same directory, so prune them together.
+    let mut installed_versions: Vec<String> = engine::installed_engines()?.into_iter()
+        .map(|(full_version, _)| full_version)
+        .collect();
+    installed_versions.dedup();
+    let mut installed_versions: Vec<(String, Version)> = installed_versions.into_iter()
+        .filter_map(|full_version| match Version::parse(&full_version) {
+            Some(version) => Some((full_version, version)),
+            None => {
+                // There's no telling whether a project uses it, so leave it to the user.
+                let engine_dir = fyg_dirs.engines_data().join(&full_version);
+                warn!("Skipping {}, since it's not a version fyg understands.", engine_dir.display());
+                None
+            }
+        })
+        .collect();
+    installed_versions.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+    // Find the newest installed version of each minor series, to keep them around by default.
+    let mut newest_versions: HashMap<(u32, u32), &Version> = HashMap::new();
+    for (_, version) in &installed_versions {
+        newest_versions.insert(version.minor_series(), version);
+    }
+
+    let mut unused = Vec::new();
+    for (full_version, version) in &installed_versions {
+        if used_versions.contains(version) {
+            continue;
+        }
+        let is_newest = newest_versions.get(&version.minor_series()) == Some(&version);
+        if is_newest && !include_newest {
+            continue;
+        }
+        unused.push(full_version.as_str());
+    }
+
+    if unused.is_empty() {
+        println!("No unused engines to prune.");
+        return Ok(());
+    }
+
+    let mut paths = Vec::new();
+    for &full_version in &unused {
+        paths.push(fyg_dirs.engines_data().join(full_version));
+        let cache_dir = fyg_dirs.engines_cache().join(full_version);
+        if cache && cache_dir.is_dir() {
+            paths.push(cache_dir);
+        }
+    }
+    println!("Unused engines:");
+    if !confirm_removal(&paths, dry_run, yes)? {
+        return Ok(());
+    }
+
+    for full_version in unused {
+        for variant in Variant::ALL {
+            if !engine_paths(fyg_dirs.engines_data(), full_version, variant)?.is_empty() {
+                uninstall(fyg_dirs.engines_data(), full_version, variant)?;
+            }
+        }
+        let cache_dir = fyg_dirs.engines_cache().join(full_version);
+        if cache && cache_dir.is_dir() {
+            fs::remove_dir_all(&cache_dir)?;
+        }
+        let version = full_version.strip_suffix("-stable")
+            .unwrap_or(full_version);
+        println!("Removed {}", version);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,519 @@
+use std::cmp::Reverse;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use octocrab::models::repos::Release;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Paragraph, Tabs},
+    DefaultTerminal, Frame,
+};
+
+use crate::{
+    cache::{self, CacheEntry},
+    cli::CacheCommand,
+    commands::{cache as cache_command, edit, install, launch, list, uninstall},
+    engine::{self, Variant},
+    output::Format,
+    registry::{KnownProject, ProjectRegistry},
+    releases,
+    version::{get_full_version, Version},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Installed,
+    Available,
+    Cache,
+    Projects,
+}
+
+impl Pane {
+    const ALL: [Pane; 4] = [Pane::Installed, Pane::Available, Pane::Cache, Pane::Projects];
+
+    fn title(self) -> &'static str {
+        match self {
+            Pane::Installed => "Installed",
+            Pane::Available => "Available",
+            Pane::Cache => "Cache",
+            Pane::Projects => "Projects",
+        }
+    }
+
+    /// Keys for the pane's actions, shown under it.
+    fn keys(self) -> &'static str {
+        match self {
+            Pane::Installed => "enter launch  d uninstall",
+            Pane::Available => "enter install  t install with templates  / filter  m mono  p prereleases",
+            Pane::Cache => "d remove version's downloads",
+            Pane::Projects => "enter open in its engine",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Something to do with the CLI's commands, which print as they go, so they run with the terminal
+/// handed back.
+enum Action {
+    Install { version: String, variant: Variant, templates: bool },
+    Uninstall(String, Variant),
+    RemoveCache(String),
+    Launch(String, Variant),
+    Edit(PathBuf),
+}
+
+impl Action {
+    /// Whether the action has output worth reading before going back to the interface. Launching
+    /// and editing just start Godot.
+    fn waits(&self) -> bool {
+        !matches!(self, Action::Launch(..) | Action::Edit(_))
+    }
+
+    async fn run(&self) -> Result<String> {
+        match self {
+            Action::Install { version, variant, templates } => {
+                install::cmd(Some(version), *variant == Variant::Mono, *templates, false).await?;
+                Ok(format!("Installed version {}{}.", version, variant_suffix(*variant)))
+            }
+            Action::Uninstall(version, variant) => {
+                // The user may say no when asked, so check what happened.
+                uninstall::cmd(version, *variant, false, false)?;
+                if engine::bin_path(&get_full_version(version), *variant).is_file() {
+                    Ok(format!("Kept version {}{}.", version, variant_suffix(*variant)))
+                } else {
+                    Ok(format!("Uninstalled version {}{}.", version, variant_suffix(*variant)))
+                }
+            }
+            Action::RemoveCache(version) => {
+                let rm = CacheCommand::Rm {
+                    all: false,
+                    versions: vec![version.clone()],
+                    dry_run: false,
+                    yes: false,
+                };
+                cache_command::cmd(&Some(rm), Format::Text)?;
+                if cache::entries()?.iter().any(|entry| entry.matches_version(version)) {
+                    Ok(format!("Kept cached downloads for version {}.", version))
+                } else {
+                    Ok(format!("Removed cached downloads for version {}.", version))
+                }
+            }
+            Action::Launch(version, variant) => {
+                launch::cmd(Some(version), *variant)?;
+                Ok(format!("Launched version {}{}.", version, variant_suffix(*variant)))
+            }
+            Action::Edit(project_dir) => {
+                edit::cmd(project_dir, false).await?;
+                Ok(format!("Opened {}.", project_dir.display()))
+            }
+        }
+    }
+}
+
+struct App {
+    pane: Pane,
+    installed: Vec<(String, Variant)>,
+    /// Every release in the user's sources, once they've been fetched.
+    releases: Option<Vec<Release>>,
+    /// The releases that pass the filter.
+    available: Vec<Release>,
+    cache_entries: Vec<CacheEntry>,
+    projects: Vec<KnownProject>,
+    /// Selection in each pane.
+    lists: [ListState; 4],
+    prefix: String,
+    editing_prefix: bool,
+    prereleases: bool,
+    variant: Variant,
+    status: String,
+    quit: bool,
+}
+
+pub async fn cmd() -> Result<()> {
+    if !io::stdout().is_terminal() || !io::stdin().is_terminal() {
+        bail!("fyg tui needs to run in a terminal.");
+    }
+
+    let mut app = App {
+        pane: Pane::Installed,
+        installed: Vec::new(),
+        releases: None,
+        available: Vec::new(),
+        cache_entries: Vec::new(),
+        projects: Vec::new(),
+        lists: Default::default(),
+        prefix: String::new(),
+        editing_prefix: false,
+        prereleases: false,
+        variant: Variant::Standard,
+        status: String::new(),
+        quit: false,
+    };
+    app.refresh()?;
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app).await;
+    ratatui::restore();
+    result
+}
+
+async fn run(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.quit {
+        // Fetch releases the first time they're looked at, saying so while it happens.
+        if app.pane == Pane::Available && app.releases.is_none() {
+            app.status = "Fetching releases...".to_string();
+            terminal.draw(|frame| app.draw(frame))?;
+            app.fetch_releases().await;
+        }
+
+        terminal.draw(|frame| app.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let Some(action) = app.handle_key(key) else {
+            continue;
+        };
+
+        // Hand the terminal back while the command runs, so its output and prompts work as usual.
+        ratatui::restore();
+        let result = action.run().await;
+        if action.waits() {
+            if let Err(err) = &result {
+                eprintln!("Error: {:#}", err);
+            }
+            print!("\nPress Enter to go back to fyg tui.");
+            io::stdout().flush()?;
+            io::stdin().read_line(&mut String::new())?;
+        }
+        *terminal = ratatui::init();
+
+        app.status = match result {
+            Ok(message) => message,
+            Err(err) => format!("Error: {:#}", err),
+        };
+        if let Err(err) = app.refresh() {
+            app.status = format!("Error: {:#}", err);
+        }
+    }
+
+    Ok(())
+}
+
+impl App {
+    /// Reload everything that's on disk, keeping the releases already fetched.
+    fn refresh(&mut self) -> Result<()> {
+        self.installed = engine::installed_engines()?;
+        self.installed.sort_by_key(|(full_version, _)| Reverse(Version::parse(full_version)));
+        self.cache_entries = cache::entries()?;
+        self.projects = ProjectRegistry::load()?.projects;
+        // Show the most recently opened projects first.
+        self.projects.sort_by_key(|project| Reverse(project.last_opened));
+        self.apply_filter();
+        Ok(())
+    }
+
+    async fn fetch_releases(&mut self) {
+        match releases::list_releases().await {
+            Ok(releases) => {
+                self.status = format!("Found {} releases.", releases.len());
+                self.releases = Some(releases);
+            }
+            Err(err) => {
+                // Don't keep trying on every key press. Refreshing tries again.
+                self.status = format!("Error: Could not fetch releases. {:#}", err);
+                self.releases = Some(Vec::new());
+            }
+        }
+        self.apply_filter();
+    }
+
+    fn apply_filter(&mut self) {
+        let prefix = Some(self.prefix.as_str()).filter(|prefix| !prefix.is_empty());
+        let filter = list::Filter::new(prefix, None, self.prereleases, false)
+            .expect("filters without a regex are always valid");
+        self.available = list::filter_releases(self.releases.clone().unwrap_or_default(), &filter);
+        for pane in Pane::ALL {
+            self.clamp_selection(pane);
+        }
+    }
+
+    fn len(&self, pane: Pane) -> usize {
+        match pane {
+            Pane::Installed => self.installed.len(),
+            Pane::Available => self.available.len(),
+            Pane::Cache => self.cache_entries.len(),
+            Pane::Projects => self.projects.len(),
+        }
+    }
+
+    fn clamp_selection(&mut self, pane: Pane) {
+        let len = self.len(pane);
+        let list = &mut self.lists[pane.index()];
+        let selected = match (list.selected(), len) {
+            (_, 0) => None,
+            (Some(selected), len) => Some(selected.min(len - 1)),
+            (None, _) => Some(0),
+        };
+        list.select(selected);
+    }
+
+    fn move_selection(&mut self, by: isize) {
+        let len = self.len(self.pane);
+        let list = &mut self.lists[self.pane.index()];
+        if let Some(selected) = list.selected() {
+            list.select(Some(selected.saturating_add_signed(by).min(len.saturating_sub(1))));
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.lists[self.pane.index()].selected()
+    }
+
+    fn switch_pane(&mut self, by: isize) {
+        let index = (self.pane.index() as isize + by).rem_euclid(Pane::ALL.len() as isize);
+        self.pane = Pane::ALL[index as usize];
+    }
+
+    /// Handle a key press, returning a command to run if it asked for one.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if self.editing_prefix {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.editing_prefix = false,
+                KeyCode::Backspace => {
+                    self.prefix.pop();
+                }
+                KeyCode::Char(c) => self.prefix.push(c),
+                _ => {}
+            }
+            self.apply_filter();
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::Right => self.switch_pane(1),
+            KeyCode::BackTab | KeyCode::Left => self.switch_pane(-1),
+            KeyCode::Char(c @ '1'..='4') => self.pane = Pane::ALL[c as usize - '1' as usize],
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Char('r') => {
+                if self.pane == Pane::Available {
+                    self.releases = None;
+                }
+                self.status = match self.refresh() {
+                    Ok(()) => "Refreshed.".to_string(),
+                    Err(err) => format!("Error: {:#}", err),
+                };
+            }
+            _ => return self.pane_action(key),
+        }
+        None
+    }
+
+    fn pane_action(&mut self, key: KeyEvent) -> Option<Action> {
+        let selected = self.selected();
+        match (self.pane, key.code) {
+            (Pane::Installed, KeyCode::Enter) => {
+                let (full_version, variant) = &self.installed[selected?];
+                Some(Action::Launch(display_version(full_version).to_string(), *variant))
+            }
+            (Pane::Installed, KeyCode::Char('d')) => {
+                let (full_version, variant) = &self.installed[selected?];
+                Some(Action::Uninstall(display_version(full_version).to_string(), *variant))
+            }
+            (Pane::Available, KeyCode::Enter | KeyCode::Char('i' | 't')) => {
+                let release = &self.available[selected?];
+                Some(Action::Install {
+                    version: display_version(&release.tag_name).to_string(),
+                    variant: self.variant,
+                    templates: key.code == KeyCode::Char('t'),
+                })
+            }
+            (Pane::Available, KeyCode::Char('/')) => {
+                self.editing_prefix = true;
+                None
+            }
+            (Pane::Available, KeyCode::Char('m')) => {
+                self.variant = match self.variant {
+                    Variant::Standard => Variant::Mono,
+                    Variant::Mono => Variant::Standard,
+                };
+                None
+            }
+            (Pane::Available, KeyCode::Char('p')) => {
+                self.prereleases = !self.prereleases;
+                self.apply_filter();
+                None
+            }
+            (Pane::Cache, KeyCode::Char('d')) => {
+                let entry = &self.cache_entries[selected?];
+                match &entry.version {
+                    Some(version) => Some(Action::RemoveCache(version.clone())),
+                    None => {
+                        self.status = format!("{} isn't a version's download. Remove it by hand.", entry.path.display());
+                        None
+                    }
+                }
+            }
+            (Pane::Projects, KeyCode::Enter) => {
+                let project = &self.projects[selected?];
+                Some(Action::Edit(project.path.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, pane_area, keys_area, status_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+
+        let titles = Pane::ALL.into_iter()
+            .enumerate()
+            .map(|(index, pane)| format!("{} {}", index + 1, pane.title()));
+        let tabs = Tabs::new(titles)
+            .select(self.pane.index())
+            .highlight_style(Style::new().bold().reversed());
+        frame.render_widget(tabs, tabs_area);
+
+        let (title, items) = match self.pane {
+            Pane::Installed => ("Installed engines".to_string(), self.installed_items()),
+            Pane::Available => (self.available_title(), self.available_items()),
+            Pane::Cache => {
+                let total_size = self.cache_entries.iter().map(|entry| entry.size).sum::<u64>();
+                let title = format!("Cached downloads ({})", humansize::format_size(total_size, humansize::DECIMAL));
+                (title, self.cache_items())
+            }
+            Pane::Projects => ("Known projects".to_string(), self.project_items()),
+        };
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, pane_area, &mut self.lists[self.pane.index()]);
+
+        let keys = if self.editing_prefix {
+            "type a version prefix  enter done".to_string()
+        } else {
+            format!("{}  r refresh  tab switch  q quit", self.pane.keys())
+        };
+        frame.render_widget(Paragraph::new(keys).dim(), keys_area);
+        frame.render_widget(Paragraph::new(self.status.as_str()), status_area);
+    }
+
+    fn installed_items(&self) -> Vec<ListItem<'static>> {
+        self.installed.iter()
+            .map(|(full_version, variant)| {
+                ListItem::new(format!("{}{}", display_version(full_version), variant_suffix(*variant)))
+            })
+            .collect()
+    }
+
+    fn available_title(&self) -> String {
+        let mut title = format!("Available releases ({})", self.variant);
+        if !self.prefix.is_empty() || self.editing_prefix {
+            title.push_str(&format!(" matching {}", self.prefix));
+            if self.editing_prefix {
+                title.push('_');
+            }
+        }
+        if self.prereleases {
+            title.push_str(" with prereleases");
+        }
+        title
+    }
+
+    fn available_items(&self) -> Vec<ListItem<'static>> {
+        self.available.iter()
+            .map(|release| {
+                // Mark releases the same way `fyg list --available` does.
+                let full_version = get_full_version(&release.tag_name);
+                let mut markers = Vec::new();
+                let installed = engine::bin_path(&full_version, self.variant).is_file();
+                if installed {
+                    markers.push("installed");
+                }
+                if list::is_cached(&full_version, self.variant) {
+                    markers.push("cached");
+                }
+                let unavailable = !list::has_archive(release, self.variant);
+                if unavailable {
+                    markers.push("unavailable");
+                }
+
+                let version = display_version(&release.tag_name);
+                let line = if markers.is_empty() {
+                    Line::from(version.to_string())
+                } else {
+                    Line::from(format!("{} ({})", version, markers.join(", ")))
+                };
+                if installed {
+                    ListItem::new(line.bold())
+                } else if unavailable {
+                    ListItem::new(line.dim())
+                } else {
+                    ListItem::new(line)
+                }
+            })
+            .collect()
+    }
+
+    fn cache_items(&self) -> Vec<ListItem<'static>> {
+        self.cache_entries.iter()
+            .map(|entry| {
+                ListItem::new(format!(
+                    "{} ({}, {}): {}",
+                    entry.version.as_deref().unwrap_or_default(),
+                    cache_command::kind_label(entry),
+                    humansize::format_size(entry.size, humansize::DECIMAL),
+                    entry.name,
+                ))
+            })
+            .collect()
+    }
+
+    fn project_items(&self) -> Vec<ListItem<'static>> {
+        self.projects.iter()
+            .map(|project| {
+                let name = project.name.as_deref().unwrap_or("(unnamed)");
+                let mut version = project.version.clone();
+                if project.mono {
+                    version.push_str(" mono");
+                }
+                if !project.is_engine_installed() {
+                    version.push_str(", not installed");
+                }
+                ListItem::new(format!("{} ({}): {}", name, version, project.path.display()))
+            })
+            .collect()
+    }
+}
+
+/// A version as fyg shows it, without a stable suffix.
+fn display_version(full_version: &str) -> &str {
+    full_version.strip_suffix("-stable")
+        .unwrap_or(full_version)
+}
+
+/// What to put after a version to say which build it is, like `fyg list` does.
+fn variant_suffix(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Standard => "",
+        Variant::Mono => " (mono)",
+    }
+}
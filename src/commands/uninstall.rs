@@ -1,15 +1,32 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 use crate::{
-    commands::uninstall,
+    commands::{confirm_removal, engine_paths, uninstall},
     dirs::FygDirs,
+    engine::Variant,
+    output::{CodedError, ErrorCode},
+    version::get_full_version,
 };
 
-pub fn cmd(version: &str) -> Result<()> {
+pub fn cmd(version: &str, variant: Variant, dry_run: bool, yes: bool) -> Result<()> {
     let fyg_dirs = FygDirs::get();
 
-    uninstall(fyg_dirs.engines_data(), version)?;
-    println!("Uninstalled version {}.", version);
+    let description = match variant {
+        Variant::Standard => format!("version {}", version),
+        Variant::Mono => format!("the Mono build of version {}", version),
+    };
+    let engine_paths = engine_paths(fyg_dirs.engines_data(), &get_full_version(version), variant)?;
+    if engine_paths.is_empty() {
+        bail!(CodedError::new(ErrorCode::NotInstalled, format!("Can't uninstall {}, it is not installed.", description)));
+    }
+
+    println!("Engine to uninstall:");
+    if !confirm_removal(&engine_paths, dry_run, yes)? {
+        return Ok(());
+    }
+
+    uninstall(fyg_dirs.engines_data(), version, variant)?;
+    println!("Uninstalled {}.", description);
 
     Ok(())
 }
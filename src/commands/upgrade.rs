@@ -0,0 +1,189 @@
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    commands::{confirm, engine_paths, install, lock::resolve, uninstall},
+    config::{ProjectFygConfig, UserConfig},
+    dirs::FygDirs,
+    engine::{self, AssetKind, Variant},
+    lock::ProjectLock,
+    output::{CodedError, ErrorCode},
+    platform::PLATFORM,
+    registry::ProjectRegistry,
+    releases,
+    version::{get_full_version, Stage, Version},
+};
+
+/// Directory in an engine's editor data that's specific to its version, so not worth copying.
+static EXPORT_TEMPLATES_DIR_NAME: &str = "export_templates";
+
+pub async fn cmd(project_fyg_dir: &Path, to: Option<&str>, yes: bool) -> Result<()> {
+    let Some(config_path) = ProjectFygConfig::find_path(project_fyg_dir) else {
+        // Let load report which files it looked for.
+        ProjectFygConfig::load(project_fyg_dir)?;
+        return Ok(());
+    };
+    let project_config = ProjectFygConfig::load(project_fyg_dir)?;
+    let Some(current_version) = Version::parse(&project_config.version) else {
+        bail!(CodedError::new(
+            ErrorCode::InvalidConfig,
+            format!("Can't upgrade from version {}.", &project_config.version),
+        ));
+    };
+
+    // Find which version to upgrade to.
+    let target_version = match to {
+        Some(to) => {
+            let Some(target_version) = Version::parse(to) else {
+                bail!("Invalid version \"{}\", expected something like \"4.3\" or \"4.3-rc2\".", to);
+            };
+            target_version
+        }
+        None => {
+            let newest_patch = releases::list_releases().await?
+                .into_iter()
+                .filter(|release| !release.prerelease)
+                .filter_map(|release| Version::parse(&release.tag_name))
+                .filter(|version| version.stage == Stage::Stable && version.minor_series() == current_version.minor_series())
+                .max();
+            match newest_patch {
+                Some(newest_patch) if newest_patch > current_version => newest_patch,
+                _ => {
+                    println!("Version {} is already the newest patch release.", &project_config.version);
+                    return Ok(());
+                }
+            }
+        }
+    };
+    if target_version == current_version {
+        println!("The project already uses version {}.", &project_config.version);
+        return Ok(());
+    }
+
+    // Write the new version the same way the old one was written.
+    let mut new_version = target_version.to_string();
+    if project_config.version.ends_with("-stable") && target_version.stage == Stage::Stable {
+        new_version.push_str("-stable");
+    }
+    println!("Upgrading from {} to {}.", &project_config.version, &new_version);
+
+    let mut new_config = project_config.clone();
+    new_config.version = new_version.clone();
+
+    // Re-lock first, so the new engine is checked against the digests it'll be locked to.
+    let new_lock = match ProjectLock::load(project_fyg_dir)? {
+        Some(_) => Some(resolve(&new_config).await?),
+        None => None,
+    };
+    let locked_sha512 = |kind, platform| new_lock.as_ref()
+        .and_then(|lock| lock.find(kind, platform, new_config.variant()))
+        .map(|asset| asset.sha512.clone());
+
+    // Install the new engine and anything else the project needs, where the project keeps it.
+    let variant = project_config.variant();
+    let engines_dir = project_config.engines_dir(project_fyg_dir);
+    let old_full_version = get_full_version(&project_config.version);
+    let new_full_version = get_full_version(&new_version);
+    if engine::bin_path_in(&engines_dir, &new_full_version, variant).is_file() {
+        println!("Version {} is already installed.", &new_version);
+    } else {
+        let expected_sha512 = locked_sha512(AssetKind::Engine, Some(PLATFORM));
+        install::install_engine_to(&engines_dir, &new_version, variant, false, expected_sha512.as_deref()).await?;
+    }
+    if project_config.templates && !engine::templates_dir_in(&engines_dir, &new_full_version, variant).is_dir() {
+        let expected_sha512 = locked_sha512(AssetKind::Templates, None);
+        install::install_templates_to(&engines_dir, &new_version, variant, false, expected_sha512.as_deref()).await?;
+    }
+
+    // Bring the editor settings along, for engines running in self-contained mode.
+    let old_editor_data = engine::engine_dir_in(&engines_dir, &old_full_version, variant).join("editor_data");
+    let new_editor_data = engine::engine_dir_in(&engines_dir, &new_full_version, variant).join("editor_data");
+    if old_editor_data.is_dir() {
+        copy_missing(&old_editor_data, &new_editor_data)
+            .context("Could not copy the editor settings.")?;
+        println!("Copied editor settings from {}.", &project_config.version);
+    }
+
+    // Only point the project at the new version once it's ready to use.
+    ProjectFygConfig::save_version(&config_path, &new_version)?;
+    println!("Updated {}.", config_path.display());
+    if let Some(new_lock) = &new_lock {
+        new_lock.save(project_fyg_dir)?;
+        println!("Updated {}.", ProjectLock::path(project_fyg_dir).display());
+    }
+
+    let mut registry = ProjectRegistry::load()?;
+    let project_path = project_fyg_dir.canonicalize()?;
+    if let Some(project) = registry.projects.iter_mut().find(|project| project.path == project_path) {
+        project.version = new_version.clone();
+        registry.save()?;
+    }
+
+    // A vendored engine belongs to this project alone, so there's nothing to share it with.
+    if project_config.vendor {
+        let old_engine_dir = engines_dir.join(&old_full_version);
+        println!("Remove {} once you no longer need version {}.", old_engine_dir.display(), &project_config.version);
+        return Ok(());
+    }
+    offer_uninstall(&registry, &project_config.version, variant, yes)
+}
+
+/// Offer to uninstall the engine the project used to use, if nothing else needs it.
+fn offer_uninstall(registry: &ProjectRegistry, old_version: &str, variant: Variant, yes: bool) -> Result<()> {
+    let old_full_version = get_full_version(old_version);
+    let engines_dir = FygDirs::get().engines_data();
+    if engine_paths(engines_dir, &old_full_version, variant)?.is_empty() {
+        return Ok(());
+    }
+
+    // The default version is launched with the standard build.
+    let is_default = variant == Variant::Standard && UserConfig::get().default_version.as_deref()
+        .is_some_and(|default_version| get_full_version(default_version) == old_full_version);
+    let is_used = registry.projects.iter()
+        .any(|project| get_full_version(&project.version) == old_full_version && project.variant() == variant);
+    if is_default || is_used {
+        println!("Keeping version {} since other projects or the default version use it.", old_version);
+        return Ok(());
+    }
+
+    if !yes {
+        // The upgrade is done, so don't fail it just because there's no one to ask.
+        if !io::stdin().is_terminal() {
+            let mono_flag = if variant == Variant::Mono { " --mono" } else { "" };
+            println!(
+                "No other project uses version {}. Run `fyg uninstall {}{}` to remove it.",
+                old_version,
+                old_version,
+                mono_flag,
+            );
+            return Ok(());
+        }
+        if !confirm(&format!("No other project uses version {}. Uninstall it?", old_version))? {
+            return Ok(());
+        }
+    }
+    uninstall(engines_dir, old_version, variant)?;
+    println!("Uninstalled {}.", old_version);
+    Ok(())
+}
+
+/// Copy the files under `from` into `to`, skipping export templates and files that already exist.
+fn copy_missing(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            if entry.file_name() != EXPORT_TEMPLATES_DIR_NAME {
+                copy_missing(&entry.path(), &target)?;
+            }
+        } else if file_type.is_file() && !target.exists() {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
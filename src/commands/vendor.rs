@@ -0,0 +1,63 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    commands::install,
+    config::ProjectFygConfig,
+    engine::{self, VENDOR_DIR_NAME},
+};
+
+static GITIGNORE_NAME: &str = ".gitignore";
+
+pub async fn cmd(project_fyg_dir: &Path, templates: bool, force: bool) -> Result<()> {
+    let project_config = ProjectFygConfig::load(project_fyg_dir)?;
+    let engines_dir = engine::vendored_engines_dir(project_fyg_dir);
+    let templates = templates || project_config.templates;
+    install::install_project_to(project_fyg_dir, &project_config, &engines_dir, templates, force).await?;
+
+    ignore_vendor_dir(project_fyg_dir)?;
+
+    if !project_config.vendor {
+        println!("Set `vendor = true` in the project's fyg.toml to have `fyg sync` and `fyg edit --install` install here too.");
+    }
+
+    Ok(())
+}
+
+/// Add the vendor directory to the project's .gitignore, unless it's already there.
+fn ignore_vendor_dir(project_fyg_dir: &Path) -> Result<()> {
+    let gitignore_path = project_fyg_dir.join(GITIGNORE_NAME);
+    let gitignore = match fs::read_to_string(&gitignore_path) {
+        Ok(gitignore) => gitignore,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Could not read {}.", gitignore_path.display()));
+        }
+    };
+    let is_ignored = gitignore.lines()
+        .map(|line| line.trim().trim_start_matches('/').trim_end_matches('/'))
+        .any(|pattern| pattern == VENDOR_DIR_NAME);
+    if is_ignored {
+        return Ok(());
+    }
+
+    // Append rather than rewrite, so nothing already in the file can be lost.
+    let mut addition = String::new();
+    if !gitignore.is_empty() && !gitignore.ends_with('\n') {
+        addition.push('\n');
+    }
+    let entry = format!("/{}/", VENDOR_DIR_NAME);
+    addition.push_str(&entry);
+    addition.push('\n');
+    fs::File::options()
+        .create(true)
+        .append(true)
+        .open(&gitignore_path)
+        .and_then(|mut file| file.write_all(addition.as_bytes()))
+        .with_context(|| format!("Could not write {}.", gitignore_path.display()))?;
+    println!("Added {} to {}.", entry, gitignore_path.display());
+    Ok(())
+}
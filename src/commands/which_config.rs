@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::config::ProjectFygConfig;
+
+pub fn cmd(project_fyg_dir: &Path) -> Result<()> {
+    let config_paths = ProjectFygConfig::find_paths(project_fyg_dir);
+    let Some((config_path, ignored_paths)) = config_paths.split_first() else {
+        // Let load report which files it looked for.
+        ProjectFygConfig::load(project_fyg_dir)?;
+        return Ok(());
+    };
+
+    let project_config = ProjectFygConfig::load_file(config_path)?;
+    println!("Version {} from {}", &project_config.version, config_path.display());
+
+    // Show what the other files would have said, to make conflicts easy to spot.
+    for ignored_path in ignored_paths {
+        match ProjectFygConfig::load_file(ignored_path) {
+            Ok(ignored_config) => println!("Ignored {} (version {})", ignored_path.display(), &ignored_config.version),
+            Err(_) => println!("Ignored {} (invalid)", ignored_path.display()),
+        }
+    }
+
+    Ok(())
+}
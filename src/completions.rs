@@ -0,0 +1,37 @@
+use clap_complete::engine::CompletionCandidate;
+
+use crate::{cache, engine, releases, version::Version};
+
+/// Versions of the installed engines, for completing `uninstall` and `launch`.
+pub fn installed_versions() -> Vec<CompletionCandidate> {
+    let installed = engine::installed_engines().unwrap_or_default();
+    candidates(installed.iter().map(|(full_version, _)| full_version.as_str()))
+}
+
+/// Versions with downloads in the cache, for completing `cache rm`.
+pub fn cached_versions() -> Vec<CompletionCandidate> {
+    let entries = cache::entries().unwrap_or_default();
+    candidates(entries.iter().filter_map(|entry| entry.tag.as_deref()))
+}
+
+/// Every version from the release index, for completing `install`. Falls back to what's cached if
+/// releases were never listed, since completing shouldn't wait on the network.
+pub fn available_versions() -> Vec<CompletionCandidate> {
+    let tags = releases::get_indexed_tags();
+    if tags.is_empty() {
+        return cached_versions();
+    }
+    candidates(tags.iter().map(String::as_str))
+}
+
+/// Turn full versions into completions without a stable suffix, newest first.
+fn candidates<'a>(full_versions: impl Iterator<Item = &'a str>) -> Vec<CompletionCandidate> {
+    let mut versions: Vec<&str> = full_versions
+        .filter(|full_version| Version::parse(full_version).is_some())
+        .collect();
+    versions.sort_by_key(|full_version| std::cmp::Reverse(Version::parse(full_version)));
+    versions.dedup();
+    versions.into_iter()
+        .map(|full_version| CompletionCandidate::new(full_version.strip_suffix("-stable").unwrap_or(full_version)))
+        .collect()
+}
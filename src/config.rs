@@ -1,36 +1,542 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use anyhow::{bail, Result, Context};
-use serde::Deserialize;
+use anyhow::{anyhow, bail, Result, Context};
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::warn;
 
+use crate::{
+    dirs::{user_config_path, FygDirs},
+    engine::{self, Variant},
+    output::{CodedError, ErrorCode},
+};
+
+/// Files a project's engine version can come from, in order of precedence. Only the TOML configs
+/// support settings other than the version.
 static PROJECT_FYG_CONFIGS: &[&str] = &[
     "fyg.toml",
     "godot_version.toml",
+    GODOT_VERSION_NAME,
+    TOOL_VERSIONS_NAME,
+];
+
+/// A plain text file containing just a version. e.g. "4.3-stable"
+static GODOT_VERSION_NAME: &str = ".godot-version";
+
+/// asdf's file listing versions for many tools. e.g. "godot 4.3-stable"
+static TOOL_VERSIONS_NAME: &str = ".tool-versions";
+
+/// Every key a project's fyg config may contain. Keep in sync with `ProjectFygConfig`.
+static PROJECT_FYG_CONFIG_KEYS: &[&str] = &[
+    "version",
+    "root",
+    "mono",
+    "templates",
+    "vendor",
 ];
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct ProjectFygConfig {
+    #[serde(deserialize_with = "deserialize_version")]
     pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub root: Option<PathBuf>,
+    /// Use the Mono build with C# support.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mono: bool,
+    /// Also install the export templates.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub templates: bool,
+    /// Install the engine into the project's .fyg directory instead of sharing it with other
+    /// projects.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub vendor: bool,
 }
 
 impl ProjectFygConfig {
-    /// Load the project's fyg TOML config file at `project_fyg_dir`, which is usually the root of
-    /// the project's git directory.
+    /// Find the paths of all the project's config files in `project_fyg_dir`, in order of
+    /// precedence.
+    pub fn find_paths(project_fyg_dir: &Path) -> Vec<PathBuf> {
+        PROJECT_FYG_CONFIGS.iter()
+            .map(|config_name| project_fyg_dir.join(config_name))
+            .filter(|config_path| config_path.is_file())
+            .filter(|config_path| {
+                // A .tool-versions file only counts if it lists Godot.
+                config_path.file_name().is_some_and(|name| name != TOOL_VERSIONS_NAME) ||
+                    fs::read_to_string(config_path).is_ok_and(|contents| find_tool_version(&contents).is_some())
+            })
+            .collect()
+    }
+
+    /// Find the path of the project's config file in `project_fyg_dir`, if any.
+    pub fn find_path(project_fyg_dir: &Path) -> Option<PathBuf> {
+        Self::find_paths(project_fyg_dir).into_iter().next()
+    }
+
+    /// Load the project's config file at `project_fyg_dir`, which is usually the root of the
+    /// project's git directory.
     pub fn load(project_fyg_dir: &Path) -> Result<ProjectFygConfig> {
-        for &config_name in PROJECT_FYG_CONFIGS {
-            let project_fyg_config_path = project_fyg_dir.join(config_name);
-            if !project_fyg_config_path.is_file() {
-                continue;
+        let config_paths = Self::find_paths(project_fyg_dir);
+        let Some(project_fyg_config_path) = config_paths.first() else {
+            bail!(CodedError::new(
+                ErrorCode::NoConfig,
+                format!(
+                    "No config file ({}) found in {}.",
+                    PROJECT_FYG_CONFIGS.join(", "),
+                    project_fyg_dir.display(),
+                ),
+            ));
+        };
+
+        for warning in Self::warnings(&config_paths) {
+            warn!("{}", warning);
+        }
+
+        Self::load_file(project_fyg_config_path)
+    }
+
+    /// Load and validate a single config file, in whichever format its name says it uses.
+    pub fn load_file(project_fyg_config_path: &Path) -> Result<ProjectFygConfig> {
+        let project_config_str = fs::read_to_string(project_fyg_config_path)
+            .with_context(|| format!("Could not read {}.", project_fyg_config_path.display()))?;
+        let config_name = project_fyg_config_path.file_name()
+            .unwrap_or_default();
+        if config_name == GODOT_VERSION_NAME {
+            Self::parse_godot_version(&project_config_str, project_fyg_config_path)
+        } else if config_name == TOOL_VERSIONS_NAME {
+            Self::parse_tool_versions(&project_config_str, project_fyg_config_path)
+        } else {
+            toml::from_str::<Self>(&project_config_str)
+                .map_err(|err| anyhow!(CodedError::new(ErrorCode::InvalidConfig, format_toml_error(&err, &project_config_str, project_fyg_config_path, PROJECT_FYG_CONFIG_KEYS))))
+        }
+    }
+
+    fn from_version(version: &str, mono: bool) -> ProjectFygConfig {
+        ProjectFygConfig {
+            version: version.to_string(),
+            root: None,
+            mono,
+            templates: false,
+            vendor: false,
+        }
+    }
+
+    /// Parse a .godot-version file, which has the version on its first line that isn't blank or a
+    /// comment.
+    fn parse_godot_version(contents: &str, path: &Path) -> Result<ProjectFygConfig> {
+        let Some((line_index, version)) = contents.lines()
+            .map(str::trim)
+            .enumerate()
+            .find(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        else {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{}: no version found.",
+                    path.display(),
+                ),
+            ));
+        };
+        if !is_valid_version(version) {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{}:{}:1: invalid version \"{}\", expected something like \"4.3\" or \"4.3-rc2\"",
+                    path.display(),
+                    line_index + 1,
+                    version,
+                ),
+            ));
+        }
+        Ok(Self::from_version(version, false))
+    }
+
+    /// Parse an asdf .tool-versions file's `godot` (or `godot-mono`) entry.
+    fn parse_tool_versions(contents: &str, path: &Path) -> Result<ProjectFygConfig> {
+        let Some((line_index, tool, version)) = find_tool_version(contents) else {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{}: no godot entry found.",
+                    path.display(),
+                ),
+            ));
+        };
+        let Some(version) = version else {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{}:{}:1: no version given for {}.",
+                    path.display(),
+                    line_index + 1,
+                    tool,
+                ),
+            ));
+        };
+        if !is_valid_version(version) {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{}:{}:1: invalid version \"{}\", expected something like \"4.3\" or \"4.3-rc2\"",
+                    path.display(),
+                    line_index + 1,
+                    version,
+                ),
+            ));
+        }
+        Ok(Self::from_version(version, tool == "godot-mono"))
+    }
+
+    /// Problems with a project's set of config files that don't stop it from loading.
+    pub fn warnings(config_paths: &[PathBuf]) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let [used_path, ignored_paths @ ..] = config_paths {
+            for ignored_path in ignored_paths {
+                warnings.push(format!(
+                    "Both {} and {} exist. Using {} and ignoring {}.",
+                    used_path.display(),
+                    ignored_path.display(),
+                    used_path.display(),
+                    ignored_path.display(),
+                ));
             }
-            let project_config_str = fs::read_to_string(&project_fyg_config_path)
-                .with_context(|| format!("Could not read {}.", project_fyg_config_path.display()))?;
-            return toml::from_str::<Self>(&project_config_str)
-                .with_context(|| format!("Could not parse {} as valid TOML.", project_fyg_config_path.display()));
         }
+        warnings
+    }
+
+    pub fn variant(&self) -> Variant {
+        Variant::from_mono(self.mono)
+    }
+
+    /// Where the project's engine is installed: its .fyg directory if it's vendored, or the
+    /// engines shared with other projects.
+    pub fn engines_dir(&self, project_fyg_dir: &Path) -> PathBuf {
+        if self.vendor {
+            engine::vendored_engines_dir(project_fyg_dir)
+        } else {
+            FygDirs::get().engines_data().to_path_buf()
+        }
+    }
 
-        // TODO: Better error reporting. Collect errors from the for loop and report them here.
-        bail!("No valid config file ({}) found in {}.", PROJECT_FYG_CONFIGS.join(", "), project_fyg_dir.display());
+    /// The directory containing the project's project.godot, resolving `root` relative to
+    /// `project_fyg_dir`.
+    pub fn godot_dir(&self, project_fyg_dir: &Path) -> PathBuf {
+        if let Some(dir) = &self.root {
+            if dir.is_relative() {
+                project_fyg_dir.join(dir)
+            } else {
+                dir.clone()
+            }
+        } else {
+            project_fyg_dir.to_owned()
+        }
+    }
+
+    /// Write this config as TOML to `path`, replacing any existing file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let config_str = toml::to_string(self)?;
+        fs::write(path, config_str)
+            .with_context(|| format!("Could not write {}.", path.display()))
+    }
+
+    /// Change the version in the config file at `path`, keeping the rest of the file as it is.
+    pub fn save_version(path: &Path, version: &str) -> Result<()> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}.", path.display()))?;
+        let config_name = path.file_name()
+            .unwrap_or_default();
+        let contents = if config_name == GODOT_VERSION_NAME || config_name == TOOL_VERSIONS_NAME {
+            // Both have the version on a single line, so just swap it out on that line.
+            let config = Self::load_file(path)?;
+            let line_index = if config_name == GODOT_VERSION_NAME {
+                contents.lines()
+                    .position(|line| line.trim() == config.version)
+            } else {
+                find_tool_version(&contents).map(|(line_index, _, _)| line_index)
+            };
+            let Some(line_index) = line_index else {
+                bail!("{}: could not find the version to replace.", path.display());
+            };
+            let mut lines: Vec<_> = contents.lines()
+                .map(str::to_string)
+                .collect();
+            lines[line_index] = lines[line_index].replacen(&config.version, version, 1);
+            let mut new_contents = lines.join("\n");
+            if contents.ends_with('\n') {
+                new_contents.push('\n');
+            }
+            new_contents
+        } else {
+            let mut doc = contents.parse::<toml_edit::DocumentMut>()
+                .with_context(|| format!("Could not parse {}.", path.display()))?;
+            match doc.get_mut("version").and_then(toml_edit::Item::as_value_mut) {
+                Some(value) => {
+                    // Keep any comment after the version.
+                    let decor = value.decor().clone();
+                    *value = toml_edit::Value::from(version);
+                    *value.decor_mut() = decor;
+                }
+                None => doc["version"] = toml_edit::value(version),
+            }
+            doc.to_string()
+        };
+        fs::write(path, contents)
+            .with_context(|| format!("Could not write {}.", path.display()))
+    }
+}
+
+/// Every key the user's global config may contain. Keep in sync with `UserConfig`.
+pub static USER_CONFIG_KEYS: &[&str] = &[
+    "default_version",
+    "home",
+    "data_dir",
+    "cache_dir",
+    "sources",
+    "mirrors",
+    "github_token",
+    "launch_args",
+    "self_contained",
+    "cache_max_size",
+    "cache_max_age_days",
+];
+
+/// The user's global fyg settings.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserConfig {
+    /// Version to use when none is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_version: Option<String>,
+    /// Directory to keep engines in its data/ subdirectory and downloads in its cache/ subdirectory,
+    /// instead of the platform's directories.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home: Option<PathBuf>,
+    /// Where to install engines instead of the platform's data directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    /// Where to cache downloads instead of the platform's cache directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+    /// GitHub repositories to look for releases in, in order. e.g. "godotengine/godot"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    /// URLs to try downloading release assets from before GitHub. `{tag}` and `{asset}` are
+    /// replaced with the release tag and asset name.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    /// Token to authenticate with GitHub, to get around rate limits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    /// Extra arguments to pass to engines when launching them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub launch_args: Vec<String>,
+    /// Whether to add an _sc_ file to installed engines so they run in self-contained mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_contained: Option<bool>,
+    /// Largest the download cache may grow to before the least recently used downloads are removed.
+    /// e.g. "5GB" or "500 MiB"
+    #[serde(deserialize_with = "deserialize_size", skip_serializing_if = "Option::is_none")]
+    pub cache_max_size: Option<String>,
+    /// Remove downloads from the cache that haven't been used for this many days.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_max_age_days: Option<u64>,
+}
+
+impl UserConfig {
+    pub fn get() -> &'static Self {
+        static USER_CONFIG: OnceLock<UserConfig> = OnceLock::new();
+        USER_CONFIG.get_or_init(|| {
+            let (user_config_path, _) = user_config_path();
+            Self::load(&user_config_path)
+                .unwrap_or_else(|err| {
+                    warn!("Ignoring user config. {:#}", err);
+                    Self::default()
+                })
+        })
+    }
+
+    /// Load the user config at `path`, or the default config if it doesn't exist.
+    pub fn load(path: &Path) -> Result<UserConfig> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let config_str = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}.", path.display()))?;
+        Self::parse(&config_str, path)
+    }
+
+    pub fn parse(config_str: &str, path: &Path) -> Result<UserConfig> {
+        toml::from_str::<Self>(config_str)
+            .map_err(|err| anyhow!(CodedError::new(ErrorCode::InvalidConfig, format_toml_error(&err, config_str, path, USER_CONFIG_KEYS))))
+    }
+
+    pub fn sources(&self) -> Vec<&str> {
+        if self.sources.is_empty() {
+            vec!["godotengine/godot"]
+        } else {
+            self.sources.iter().map(String::as_str).collect()
+        }
+    }
+
+    pub fn self_contained(&self) -> bool {
+        self.self_contained.unwrap_or(true)
+    }
+
+    /// The most the download cache may hold, in bytes.
+    pub fn cache_max_size(&self) -> Option<u64> {
+        self.cache_max_size.as_deref()
+            .and_then(parse_size)
+    }
+}
+
+/// Find the key in `keys` closest to a misspelled one, if any are only a typo or two away.
+pub fn closest_key<'a>(unknown_key: &str, keys: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (unknown_key.len() / 3).max(1);
+    keys.iter()
+        .map(|&key| (key, strsim::damerau_levenshtein(unknown_key, key)))
+        .filter(|&(_, distance)| distance <= max_distance)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(key, _)| key)
+}
+
+/// Find the Godot entry in a .tool-versions file, returning its line index, tool name, and first
+/// version.
+fn find_tool_version(contents: &str) -> Option<(usize, &str, Option<&str>)> {
+    contents.lines()
+        .enumerate()
+        .find_map(|(line_index, line)| {
+            let line = line.split_once('#').map_or(line, |(line, _)| line);
+            let mut words = line.split_whitespace();
+            let tool = words.next()?;
+            (tool == "godot" || tool == "godot-mono")
+                .then(|| (line_index, tool, words.next()))
+        })
+}
+
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let size = String::deserialize(deserializer)?;
+    if parse_size(&size).is_none() {
+        return Err(serde::de::Error::custom(format!(
+            "invalid size \"{}\", expected something like \"5GB\" or \"500 MiB\"",
+            size,
+        )));
+    }
+    Ok(Some(size))
+}
+
+/// Parse a size in bytes like "5GB", "500 MiB" or "1024".
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+fn deserialize_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let version = String::deserialize(deserializer)?;
+    if !is_valid_version(&version) {
+        return Err(serde::de::Error::custom(format!(
+            "invalid version \"{}\", expected something like \"4.3\" or \"4.3-rc2\"",
+            version,
+        )));
+    }
+    Ok(version)
+}
+
+fn is_valid_version(version: &str) -> bool {
+    let number = version.split_once('-')
+        .map_or(version, |(number, _)| number);
+    !number.is_empty() &&
+        number.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Format a TOML error as `path:line:column: message`, followed by the offending line and a
+/// suggestion for misspelled keys.
+fn format_toml_error(err: &toml::de::Error, contents: &str, path: &Path, keys: &[&str]) -> String {
+    let mut message = err.message().to_string();
+
+    if let Some(unknown_key) = message.strip_prefix("unknown field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(key, _)| key)
+    {
+        message = match closest_key(unknown_key, keys) {
+            Some(key) => format!("unknown key `{}`, did you mean `{}`?", unknown_key, key),
+            None => format!("unknown key `{}`, expected one of: {}", unknown_key, keys.join(", ")),
+        };
+    }
+
+    let Some(span) = err.span() else {
+        return format!("{}: {}", path.display(), message);
+    };
+
+    // Convert the byte span to a 1-based line and column.
+    let before = &contents[..span.start];
+    let line_index = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    let line = contents.lines().nth(line_index).unwrap_or_default();
+    let underline_len = contents[span.clone()].lines().next()
+        .map_or(1, |spanned| spanned.chars().count().max(1));
+
+    format!(
+        "{}:{}:{}: {}\n  |\n  | {}\n  | {}{}",
+        path.display(),
+        line_index + 1,
+        column,
+        message,
+        line,
+        " ".repeat(column - 1),
+        "^".repeat(underline_len),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_without_a_unit_are_bytes() {
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("12b"), Some(12));
+    }
+
+    #[test]
+    fn decimal_and_binary_units() {
+        assert_eq!(parse_size("5KB"), Some(5_000));
+        assert_eq!(parse_size("5GB"), Some(5_000_000_000));
+        assert_eq!(parse_size("2TB"), Some(2_000_000_000_000));
+        assert_eq!(parse_size("1KiB"), Some(1024));
+        assert_eq!(parse_size("500 MiB"), Some(500 * 1024 * 1024));
+        assert_eq!(parse_size("1.5GiB"), Some(3 << 29));
+    }
+
+    #[test]
+    fn units_ignore_case_and_surrounding_space() {
+        assert_eq!(parse_size(" 5gb "), Some(5_000_000_000));
+        assert_eq!(parse_size("5 mib"), Some(5 << 20));
+    }
+
+    #[test]
+    fn invalid_sizes_are_rejected() {
+        for size in ["", "GB", "5 GB extra", "5XB", "-5GB", "five"] {
+            assert_eq!(parse_size(size), None, "{:?} should not parse", size);
+        }
     }
 }
@@ -1,46 +1,138 @@
+use std::env;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 use directories::BaseDirs;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::UserConfig;
 
 const FYG_DIR: &str = "find-your-godot";
 
+const USER_CONFIG_NAME: &str = "config.toml";
+
+const PROJECTS_REGISTRY_NAME: &str = "projects.toml";
+
+/// Environment variable pointing at a directory that holds fyg's config, data and cache.
+pub const FYG_HOME_VAR: &str = "FYG_HOME";
+
+/// Environment variable pointing at where to install engines. Takes precedence over FYG_HOME.
+pub const FYG_DATA_DIR_VAR: &str = "FYG_DATA_DIR";
+
+/// Environment variable pointing at where to cache downloads. Takes precedence over FYG_HOME.
+pub const FYG_CACHE_DIR_VAR: &str = "FYG_CACHE_DIR";
+
+/// File that puts fyg in portable mode when it's next to the fyg executable, like Godot's `_sc_`.
+const PORTABLE_MARKER_NAME: &str = "_fyg_portable_";
+
+/// Directory next to the fyg executable that holds everything in portable mode.
+const PORTABLE_HOME_NAME: &str = "fyg_home";
+
+/// Subdirectories of a fyg home directory.
+const HOME_DATA_DIR: &str = "data";
+const HOME_CACHE_DIR: &str = "cache";
+
+/// What decided where one of fyg's directories is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "lowercase")]
+pub enum DirSource {
+    /// An environment variable, e.g. FYG_HOME.
+    Env(&'static str),
+    /// A key in the user config, e.g. data_dir.
+    Config(&'static str),
+    /// A directory next to the fyg executable, since it's in portable mode.
+    Portable,
+    /// The platform's usual directory, under the user's home directory.
+    Default,
+    /// A temporary directory, since the user has no home directory.
+    Fallback,
+}
+
+impl fmt::Display for DirSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirSource::Env(var) => write!(f, "{} environment variable", var),
+            DirSource::Config(key) => write!(f, "{} in the user config", key),
+            DirSource::Portable => write!(f, "portable mode"),
+            DirSource::Default => write!(f, "platform default"),
+            DirSource::Fallback => write!(f, "fallback, since there's no home directory"),
+        }
+    }
+}
+
 pub struct FygDirs {
+    data_dir: PathBuf,
+    data_source: DirSource,
+    cache_dir: PathBuf,
+    cache_source: DirSource,
     engines_data_dir: PathBuf,
     engines_cache_dir: PathBuf,
+    projects_registry_path: PathBuf,
 }
 
 impl FygDirs {
     pub fn get() -> &'static Self {
         static DIRS: OnceLock<FygDirs> = OnceLock::new();
-        DIRS.get_or_init(|| Self::new())
+        DIRS.get_or_init(Self::new)
     }
 
     pub fn new() -> Self {
-        let Some(base_dirs) = BaseDirs::new() else {
-            return Self {
-                engines_data_dir: PathBuf::new(),
-                engines_cache_dir: PathBuf::new(),
-            }
-        };
-
-        let mut engines_cache_dir = base_dirs.cache_dir()
-            .join(FYG_DIR);
-        // Add an intermediate cache directory on Windows since it's placed in ~/AppData/Local
-        // with other things by default.
-        if cfg!(target_os = "windows") {
-            engines_cache_dir.push("cache");
+        let user_config = UserConfig::get();
+        let base_dirs = BaseDirs::new();
+
+        let (data_dir, data_source) = resolve_dir(
+            FYG_DATA_DIR_VAR,
+            ("data_dir", user_config.data_dir.as_deref()),
+            HOME_DATA_DIR,
+            || base_dirs.as_ref().map(|base_dirs| base_dirs.data_dir().join(FYG_DIR)),
+        );
+        let (cache_dir, cache_source) = resolve_dir(
+            FYG_CACHE_DIR_VAR,
+            ("cache_dir", user_config.cache_dir.as_deref()),
+            HOME_CACHE_DIR,
+            || base_dirs.as_ref().map(|base_dirs| {
+                let mut cache_dir = base_dirs.cache_dir()
+                    .join(FYG_DIR);
+                // Add an intermediate cache directory on Windows since it's placed in ~/AppData/Local
+                // with other things by default.
+                if cfg!(target_os = "windows") {
+                    cache_dir.push("cache");
+                }
+                cache_dir
+            }),
+        );
+
+        if data_source == DirSource::Fallback || cache_source == DirSource::Fallback {
+            warn!(
+                "Could not find your home directory, so using {}. Set {} to choose where fyg keeps its files.",
+                fallback_home().display(),
+                FYG_HOME_VAR,
+            );
         }
-        engines_cache_dir.push("engines");
 
         Self {
-            engines_data_dir: base_dirs.data_dir()
-                .join(FYG_DIR)
-                .join("engines"),
-            engines_cache_dir,
+            engines_data_dir: data_dir.join("engines"),
+            engines_cache_dir: cache_dir.join("engines"),
+            projects_registry_path: data_dir.join(PROJECTS_REGISTRY_NAME),
+            data_dir,
+            data_source,
+            cache_dir,
+            cache_source,
         }
     }
 
+    /// Directory fyg installs engines and keeps its project list in, and what decided it.
+    pub fn data(&self) -> (&Path, DirSource) {
+        (&self.data_dir, self.data_source)
+    }
+
+    /// Directory fyg caches downloads in, and what decided it.
+    pub fn cache(&self) -> (&Path, DirSource) {
+        (&self.cache_dir, self.cache_source)
+    }
+
     pub fn engines_data(&self) -> &Path {
         &self.engines_data_dir
     }
@@ -49,8 +141,74 @@ impl FygDirs {
         &self.engines_cache_dir
     }
 
-    pub fn is_valid(&self) -> bool {
-        !self.engines_cache_dir.as_os_str().is_empty() &&
-            !self.engines_data_dir.as_os_str().is_empty()
+    /// File that records the projects fyg has opened.
+    pub fn projects_registry(&self) -> &Path {
+        &self.projects_registry_path
+    }
+}
+
+/// Work out one of fyg's directories. Environment variables win over the user config, and a
+/// directory-specific setting wins over a home directory from the same place. Portable mode only
+/// replaces the platform's directories, so it can still be pointed elsewhere.
+fn resolve_dir(
+    var: &'static str,
+    (key, config_dir): (&'static str, Option<&Path>),
+    home_subdir: &str,
+    platform_dir: impl FnOnce() -> Option<PathBuf>,
+) -> (PathBuf, DirSource) {
+    if let Some(dir) = env_path(var) {
+        return (dir, DirSource::Env(var));
+    }
+    if let Some(home) = env_path(FYG_HOME_VAR) {
+        return (home.join(home_subdir), DirSource::Env(FYG_HOME_VAR));
+    }
+    if let Some(dir) = config_dir {
+        return (dir.to_path_buf(), DirSource::Config(key));
+    }
+    if let Some(home) = &UserConfig::get().home {
+        return (home.join(home_subdir), DirSource::Config("home"));
+    }
+    if let Some(home) = portable_home() {
+        return (home.join(home_subdir), DirSource::Portable);
+    }
+    match platform_dir() {
+        Some(dir) => (dir, DirSource::Default),
+        None => (fallback_home().join(home_subdir), DirSource::Fallback),
+    }
+}
+
+/// Read a path from an environment variable, treating an empty one as unset.
+fn env_path(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Where fyg keeps everything in portable mode, if the marker file is next to its executable.
+fn portable_home() -> Option<PathBuf> {
+    let exe_path = env::current_exe().ok()?;
+    let exe_dir = exe_path.parent()?;
+    exe_dir.join(PORTABLE_MARKER_NAME)
+        .is_file()
+        .then(|| exe_dir.join(PORTABLE_HOME_NAME))
+}
+
+/// Where fyg keeps everything when the user has no home directory, e.g. in some CI containers.
+fn fallback_home() -> PathBuf {
+    env::temp_dir().join(FYG_DIR)
+}
+
+/// Path to the user's global fyg config file, and what decided it. This doesn't go through
+/// `FygDirs` since the config can change where `FygDirs` points.
+pub fn user_config_path() -> (PathBuf, DirSource) {
+    if let Some(home) = env_path(FYG_HOME_VAR) {
+        return (home.join(USER_CONFIG_NAME), DirSource::Env(FYG_HOME_VAR));
+    }
+    if let Some(home) = portable_home() {
+        return (home.join(USER_CONFIG_NAME), DirSource::Portable);
+    }
+    match BaseDirs::new() {
+        Some(base_dirs) => (base_dirs.config_dir().join(FYG_DIR).join(USER_CONFIG_NAME), DirSource::Default),
+        None => (fallback_home().join(USER_CONFIG_NAME), DirSource::Fallback),
     }
 }
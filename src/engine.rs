@@ -0,0 +1,241 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dirs::FygDirs,
+    platform::{PLATFORM, Platform},
+};
+
+/// File an install records which archive it came from in, inside the engine's directory.
+static INSTALL_RECEIPT_NAME: &str = ".fyg-receipt.toml";
+
+/// File next to an engine's binary that makes Godot run in self-contained mode:
+/// https://docs.godotengine.org/en/latest/tutorials/io/data_paths.html#self-contained-mode
+pub static SELF_CONTAINED_NAME: &str = "_sc_";
+
+/// Directory inside a project that vendored engines are installed to.
+pub static VENDOR_DIR_NAME: &str = ".fyg";
+
+/// Which build of a Godot engine version to use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// The build with C# support.
+    Mono,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 2] = [Variant::Standard, Variant::Mono];
+
+    pub fn from_mono(mono: bool) -> Self {
+        if mono {
+            Variant::Mono
+        } else {
+            Variant::Standard
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variant::Standard => write!(f, "standard"),
+            Variant::Mono => write!(f, "mono"),
+        }
+    }
+}
+
+/// What a release asset contains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetKind {
+    Engine,
+    Templates,
+}
+
+fn platform_suffix(platform: Platform, full_version: &str) -> &'static str {
+    if full_version.starts_with('4') {
+        match platform {
+            Platform::Windows32 => "win32.exe",
+            Platform::Windows64 => "win64.exe",
+            Platform::MacOS => "macos.universal",
+            Platform::Linux32 => "linux.x86_32",
+            Platform::Linux64 => "linux.x86_64",
+            Platform::Unsupported => "unsupported",
+        }
+    } else {
+        platform.to_package()
+    }
+}
+
+/// Mono builds are zipped in a directory whose name uses a slightly different platform suffix.
+fn mono_platform_suffix(platform: Platform, full_version: &str) -> &'static str {
+    if full_version.starts_with('4') {
+        match platform {
+            Platform::Windows32 => "win32",
+            Platform::Windows64 => "win64",
+            Platform::MacOS => "macos.universal",
+            Platform::Linux32 => "linux_x86_32",
+            Platform::Linux64 => "linux_x86_64",
+            Platform::Unsupported => "unsupported",
+        }
+    } else {
+        match platform {
+            Platform::Windows32 => "win32",
+            Platform::Windows64 => "win64",
+            Platform::MacOS => "osx.universal",
+            Platform::Linux32 => "x11_32",
+            Platform::Linux64 => "x11_64",
+            Platform::Unsupported => "unsupported",
+        }
+    }
+}
+
+/// Name of the directory Mono builds are packaged in. Standard builds aren't in a directory.
+fn mono_dir_name(platform: Platform, full_version: &str) -> String {
+    format!("Godot_v{}_mono_{}", full_version, mono_platform_suffix(platform, full_version))
+}
+
+pub fn get_binary_name(full_version: &str, variant: Variant) -> String {
+    // TODO: The naming convention for binary/zip names seems to change a lot. To support all
+    // versions, might be best to use a static list that we generate.
+    let platform_suffix = platform_suffix(PLATFORM, full_version);
+    match variant {
+        Variant::Standard => format!("Godot_v{}_{}", full_version, platform_suffix),
+        Variant::Mono => format!("Godot_v{}_mono_{}", full_version, platform_suffix),
+    }
+}
+
+/// Name of the release asset containing the engine for this platform.
+pub fn get_archive_name(full_version: &str, variant: Variant) -> String {
+    get_archive_name_for(PLATFORM, full_version, variant)
+}
+
+/// Name of the release asset containing the engine for the given platform.
+pub fn get_archive_name_for(platform: Platform, full_version: &str, variant: Variant) -> String {
+    match variant {
+        Variant::Standard => format!("Godot_v{}_{}.zip", full_version, platform_suffix(platform, full_version)),
+        Variant::Mono => format!("{}.zip", mono_dir_name(platform, full_version)),
+    }
+}
+
+/// Name of the release asset containing the export templates.
+pub fn get_templates_name(full_version: &str, variant: Variant) -> String {
+    match variant {
+        Variant::Standard => format!("Godot_v{}_export_templates.tpz", full_version),
+        Variant::Mono => format!("Godot_v{}_mono_export_templates.tpz", full_version),
+    }
+}
+
+/// Directory an engine version is installed to. Mono builds are installed alongside the standard
+/// build in their own subdirectory.
+pub fn engine_dir(full_version: &str, variant: Variant) -> PathBuf {
+    engine_dir_in(FygDirs::get().engines_data(), full_version, variant)
+}
+
+/// Directory an engine version is installed to under `engines_dir`, e.g. a project's vendored
+/// engines.
+pub fn engine_dir_in(engines_dir: &Path, full_version: &str, variant: Variant) -> PathBuf {
+    let version_dir = engines_dir.join(full_version);
+    match variant {
+        Variant::Standard => version_dir,
+        Variant::Mono => version_dir.join(mono_dir_name(PLATFORM, full_version)),
+    }
+}
+
+pub fn bin_path(full_version: &str, variant: Variant) -> PathBuf {
+    bin_path_in(FygDirs::get().engines_data(), full_version, variant)
+}
+
+pub fn bin_path_in(engines_dir: &Path, full_version: &str, variant: Variant) -> PathBuf {
+    engine_dir_in(engines_dir, full_version, variant)
+        .join(get_binary_name(full_version, variant))
+}
+
+/// Directory Godot looks for export templates in when running in self-contained mode.
+pub fn templates_dir(full_version: &str, variant: Variant) -> PathBuf {
+    templates_dir_in(FygDirs::get().engines_data(), full_version, variant)
+}
+
+pub fn templates_dir_in(engines_dir: &Path, full_version: &str, variant: Variant) -> PathBuf {
+    let mut templates_version = full_version.replace('-', ".");
+    if variant == Variant::Mono {
+        templates_version.push_str(".mono");
+    }
+    engine_dir_in(engines_dir, full_version, variant)
+        .join("editor_data")
+        .join("export_templates")
+        .join(templates_version)
+}
+
+/// Directory a project's vendored engines are installed to.
+pub fn vendored_engines_dir(project_fyg_dir: &Path) -> PathBuf {
+    project_fyg_dir.join(VENDOR_DIR_NAME).join("engines")
+}
+
+/// Whether the installed engine runs in self-contained mode.
+pub fn is_self_contained(full_version: &str, variant: Variant) -> bool {
+    engine_dir(full_version, variant)
+        .join(SELF_CONTAINED_NAME)
+        .is_file()
+}
+
+/// Every engine installed in the engines data dir, as full versions and variants.
+pub fn installed_engines() -> Result<Vec<(String, Variant)>> {
+    let engines_data_dir = FygDirs::get().engines_data();
+    if !engines_data_dir.is_dir() {
+        // Engines directory doesn't exist, so no engines installed.
+        return Ok(Vec::new());
+    }
+
+    let mut installed = Vec::new();
+    for entry in fs::read_dir(engines_data_dir)? {
+        let entry = entry?;
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let full_version = entry.file_name().to_string_lossy().into_owned();
+        for variant in Variant::ALL {
+            // TODO: Also check that it's executable?
+            if bin_path(&full_version, variant).is_file() {
+                installed.push((full_version.clone(), variant));
+            }
+        }
+    }
+
+    Ok(installed)
+}
+
+/// Records which archive an engine was installed from, so it can be checked against a lockfile.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct InstallReceipt {
+    pub archive: String,
+    pub sha512: String,
+}
+
+impl InstallReceipt {
+    /// Load the receipt for the engine installed in `engine_dir`, if it has one.
+    pub fn load(engine_dir: &Path) -> Result<Option<InstallReceipt>> {
+        let receipt_path = engine_dir.join(INSTALL_RECEIPT_NAME);
+        if !receipt_path.is_file() {
+            return Ok(None);
+        }
+        let receipt_str = fs::read_to_string(&receipt_path)
+            .with_context(|| format!("Could not read {}.", receipt_path.display()))?;
+        let receipt = toml::from_str(&receipt_str)
+            .with_context(|| format!("Could not parse {}.", receipt_path.display()))?;
+        Ok(Some(receipt))
+    }
+
+    pub fn save(&self, engine_dir: &Path) -> Result<()> {
+        let receipt_path = engine_dir.join(INSTALL_RECEIPT_NAME);
+        fs::write(&receipt_path, toml::to_string(self)?)
+            .with_context(|| format!("Could not write {}.", receipt_path.display()))
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    config::ProjectFygConfig,
+    engine::{self, AssetKind, InstallReceipt, Variant},
+    output::{CodedError, ErrorCode},
+    platform::{PLATFORM, Platform},
+    version::get_full_version,
+};
+
+static PROJECT_LOCK_NAME: &str = "fyg.lock";
+
+static PROJECT_LOCK_HEADER: &str = "# This file is generated by `fyg lock`. Do not edit it by hand.\n\n";
+
+/// The exact release a project's version requirement resolved to, and the digests of the archives
+/// it may be installed from.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectLock {
+    /// The version requirement from the project's fyg config when it was locked.
+    pub version: String,
+    /// The release tag the requirement resolved to. e.g. "4.3-stable"
+    pub tag: String,
+    #[serde(default, rename = "asset")]
+    pub assets: Vec<LockedAsset>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LockedAsset {
+    pub name: String,
+    pub kind: AssetKind,
+    /// Platform the asset is for. Export templates are for every platform.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Platform>,
+    pub variant: Variant,
+    pub sha512: String,
+    /// URL the asset was resolved from.
+    pub source: String,
+}
+
+impl ProjectLock {
+    pub fn path(project_fyg_dir: &Path) -> PathBuf {
+        project_fyg_dir.join(PROJECT_LOCK_NAME)
+    }
+
+    /// Load the project's fyg.lock, if it has one.
+    pub fn load(project_fyg_dir: &Path) -> Result<Option<ProjectLock>> {
+        let lock_path = Self::path(project_fyg_dir);
+        if !lock_path.is_file() {
+            return Ok(None);
+        }
+        let lock_str = fs::read_to_string(&lock_path)
+            .with_context(|| format!("Could not read {}.", lock_path.display()))?;
+        let lock = toml::from_str(&lock_str)
+            .with_context(|| format!("Could not parse {}.", lock_path.display()))?;
+        Ok(Some(lock))
+    }
+
+    pub fn save(&self, project_fyg_dir: &Path) -> Result<()> {
+        let lock_path = Self::path(project_fyg_dir);
+        let lock_str = format!("{}{}", PROJECT_LOCK_HEADER, toml::to_string(self)?);
+        fs::write(&lock_path, lock_str)
+            .with_context(|| format!("Could not write {}.", lock_path.display()))
+    }
+
+    /// Check that this lock was generated for the project's current config.
+    pub fn check(&self, project_config: &ProjectFygConfig) -> Result<()> {
+        if self.version != project_config.version {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{} was generated for version {}, but the project asks for {}. Run `fyg lock` to update it.",
+                    PROJECT_LOCK_NAME,
+                    self.version,
+                    project_config.version,
+                ),
+            ));
+        }
+
+        let variant = project_config.variant();
+        if self.find(AssetKind::Engine, Some(PLATFORM), variant).is_none() {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{} has no {} engine for this platform. Run `fyg lock --update` to update it.",
+                    PROJECT_LOCK_NAME,
+                    variant,
+                ),
+            ));
+        }
+        if project_config.templates && self.find(AssetKind::Templates, None, variant).is_none() {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{} has no export templates. Run `fyg lock --update` to update it.",
+                    PROJECT_LOCK_NAME,
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Check that the installed engine was installed from the archive this lock pins.
+    pub fn verify_installed(&self, engines_dir: &Path, variant: Variant) -> Result<()> {
+        let Some(locked_asset) = self.find(AssetKind::Engine, Some(PLATFORM), variant) else {
+            bail!(CodedError::new(
+                ErrorCode::InvalidConfig,
+                format!(
+                    "{} has no {} engine for this platform.",
+                    PROJECT_LOCK_NAME,
+                    variant,
+                ),
+            ));
+        };
+
+        let engine_dir = engine::engine_dir_in(engines_dir, &get_full_version(&self.tag), variant);
+        match InstallReceipt::load(&engine_dir)? {
+            Some(receipt) if receipt.sha512 == locked_asset.sha512 => Ok(()),
+            Some(_) => bail!(CodedError::new(
+                ErrorCode::ChecksumMismatch,
+                format!(
+                    "The installed {} engine does not match {}. Run `fyg sync --force` to re-install it.",
+                    self.tag,
+                    PROJECT_LOCK_NAME,
+                ),
+            )),
+            None => {
+                warn!(
+                    "Can't check the installed {} engine against {} since it was installed by an older fyg.",
+                    self.tag,
+                    PROJECT_LOCK_NAME,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    pub fn find(&self, kind: AssetKind, platform: Option<Platform>, variant: Variant) -> Option<&LockedAsset> {
+        self.assets.iter()
+            .find(|asset| asset.kind == kind && asset.platform == platform && asset.variant == variant)
+    }
+}
@@ -0,0 +1,128 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{Context, Result};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::{
+    fmt::{format::{FmtSpan, Writer}, FmtContext, FormatEvent, FormatFields, FormattedFields},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+/// Environment variable with a filter for what to log, e.g. "fyg=debug". Overrides -v and -q.
+pub static FYG_LOG_VAR: &str = "FYG_LOG";
+
+static VERBOSITY: OnceLock<i8> = OnceLock::new();
+
+/// How much to show, from -q (-1) to -vvv (3).
+pub fn verbosity() -> i8 {
+    VERBOSITY.get().copied().unwrap_or_default()
+}
+
+/// Whether -q was passed, so only errors should be shown.
+pub fn is_quiet() -> bool {
+    verbosity() < 0
+}
+
+/// Print a status message to stdout, e.g. where a download went, unless -q was passed.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::logging::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use status;
+
+/// What to log at each verbosity. Dependencies' events only show from -vv, since fyg already
+/// reports the errors they run into, e.g. octocrab's failed requests.
+fn default_directives(verbosity: i8) -> &'static str {
+    match verbosity {
+        i8::MIN..=-1 => "fyg=error",
+        0 => "fyg=warn",
+        1 => "fyg=info",
+        2 => "warn,fyg=debug",
+        3.. => "debug,fyg=trace",
+    }
+}
+
+fn filter(verbosity: i8) -> EnvFilter {
+    match env::var(FYG_LOG_VAR) {
+        Ok(directives) if !directives.is_empty() => EnvFilter::new(directives),
+        _ => EnvFilter::new(default_directives(verbosity)),
+    }
+}
+
+/// Send logs to stderr, and to `log_file` if given. The log file gets at least fyg's debug events,
+/// with timestamps and how long each span took, so failing runs can be looked into afterwards.
+pub fn init(verbose: u8, quiet: bool, log_file: Option<&Path>) -> Result<()> {
+    let verbosity = if quiet { -1 } else { verbose.min(3) as i8 };
+    VERBOSITY.get_or_init(|| verbosity);
+
+    let console_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        // Spans' fields are formatted once for every layer, so keep colors out of the log file too.
+        .with_ansi(false)
+        .event_format(ConsoleFormat)
+        .with_filter(filter(verbosity));
+
+    let file_layer = log_file.map(|log_file_path| {
+        let log_file = fs::File::options()
+            .create(true)
+            .append(true)
+            .open(log_file_path)
+            .with_context(|| format!("Could not open log file {}.", log_file_path.display()))?;
+        anyhow::Ok(tracing_subscriber::fmt::layer()
+            .with_writer(Mutex::new(log_file))
+            .with_ansi(false)
+            // Record how long each span took, to see where a slow install spent its time.
+            .with_span_events(FmtSpan::CLOSE)
+            .with_filter(filter(verbosity.max(2))))
+    }).transpose()?;
+
+    tracing_subscriber::registry()
+        .with(console_layer)
+        .with(file_layer)
+        .try_init()?;
+
+    Ok(())
+}
+
+/// Writes warnings and errors the way fyg always has, e.g. "Warning: ...", and anything more
+/// detailed with its level and the spans it happened in.
+struct ConsoleFormat;
+
+impl<S, N> FormatEvent<S, N> for ConsoleFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        match *event.metadata().level() {
+            Level::ERROR => write!(writer, "Error: ")?,
+            Level::WARN => write!(writer, "Warning: ")?,
+            level => {
+                write!(writer, "{:>5} ", level)?;
+                if let Some(scope) = ctx.event_scope() {
+                    for span in scope.from_root() {
+                        write!(writer, "{}", span.name())?;
+                        let extensions = span.extensions();
+                        if let Some(fields) = extensions.get::<FormattedFields<N>>() {
+                            if !fields.is_empty() {
+                                write!(writer, "{{{}}}", fields)?;
+                            }
+                        }
+                        write!(writer, ": ")?;
+                    }
+                }
+            }
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
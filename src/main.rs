@@ -1,24 +1,59 @@
-use anyhow::{bail, Result};
+use std::process::ExitCode;
 
+use anyhow::Result;
+
+use crate::output::Format;
+
+mod cache;
+mod checksum;
 mod cli;
 mod commands;
+mod completions;
 mod config;
 mod dirs;
+mod engine;
+mod lock;
+mod logging;
+mod output;
 mod platform;
+mod project;
+mod registry;
+mod releases;
 mod version;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    use clap::Parser;
+async fn main() -> Result<ExitCode> {
+    use clap::{CommandFactory, Parser};
+
+    // Answer the shell's completion requests before doing anything else.
+    clap_complete::CompleteEnv::with_factory(cli::Cli::command)
+        .var(commands::COMPLETE_VAR)
+        .complete();
 
     let cli = cli::Cli::parse();
 
-    // Initialize dirs and verify that it succeeded.
-    if !dirs::FygDirs::get().is_valid() {
-        bail!("Could not initialize app directories.");
+    match run(&cli).await {
+        Ok(exit_code) => Ok(exit_code),
+        Err(err) if cli.format() != Format::Text => {
+            output::print_error(&err);
+            Ok(ExitCode::FAILURE)
+        }
+        Err(err) => Err(err),
     }
+}
+
+/// Run the command, returning the status to exit with for commands that report results through it,
+/// like `outdated --check`.
+async fn run(cli: &cli::Cli) -> Result<ExitCode> {
+    logging::init(cli.verbose, cli.quiet, cli.log_file.as_deref())?;
 
-    commands::run_command(&cli.command).await?;
+    // Authenticate with GitHub if the user gave us a token.
+    if let Some(github_token) = &config::UserConfig::get().github_token {
+        let octocrab = octocrab::Octocrab::builder()
+            .personal_token(github_token.clone())
+            .build()?;
+        octocrab::initialise(octocrab);
+    }
 
-    Ok(())
+    commands::run_command(&cli.command, cli.format()).await
 }
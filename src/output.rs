@@ -0,0 +1,124 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// How commands print their results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Human readable text.
+    #[default]
+    Text,
+    /// A single JSON document.
+    Json,
+    /// One JSON object per line, printed as results come in.
+    Ndjson,
+}
+
+/// Stable codes for errors reported as JSON, so scripts don't have to match on messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The requested engine version isn't installed.
+    NotInstalled,
+    /// The requested engine version or release asset doesn't exist.
+    NotFound,
+    /// No project config was found.
+    NoConfig,
+    /// A config or lock file is invalid.
+    InvalidConfig,
+    /// A download didn't match its expected digest.
+    ChecksumMismatch,
+    /// Talking to GitHub or a mirror failed.
+    Network,
+    /// Reading or writing files failed.
+    Io,
+    /// Anything else.
+    Other,
+}
+
+/// An error with an `ErrorCode`. Create with `bail!(CodedError::new(...))`.
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    message: String,
+}
+
+impl CodedError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+/// Work out an error's code from the first error in its chain that has one.
+pub fn error_code(err: &anyhow::Error) -> ErrorCode {
+    for cause in err.chain() {
+        if let Some(coded_err) = cause.downcast_ref::<CodedError>() {
+            return coded_err.code;
+        }
+        if cause.is::<std::io::Error>() {
+            return ErrorCode::Io;
+        }
+        if cause.is::<reqwest::Error>() || cause.is::<octocrab::Error>() {
+            return ErrorCode::Network;
+        }
+        if cause.is::<toml::de::Error>() {
+            return ErrorCode::InvalidConfig;
+        }
+    }
+    ErrorCode::Other
+}
+
+#[derive(Serialize)]
+struct ErrorOutput<'a> {
+    error: ErrorBody<'a>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: ErrorCode,
+    message: &'a str,
+}
+
+/// Print an error as a JSON object, in the same place results would go.
+pub fn print_error(err: &anyhow::Error) {
+    let message = format!("{:#}", err);
+    let output = ErrorOutput {
+        error: ErrorBody {
+            code: error_code(err),
+            message: &message,
+        },
+    };
+    println!("{}", serde_json::to_string(&output).unwrap_or_default());
+}
+
+/// Print one record as a line of JSON.
+pub fn print_json_line(record: &impl Serialize) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string(record)?);
+    Ok(())
+}
+
+/// Print a list of records as a JSON array or as JSON lines.
+pub fn print_records(format: Format, records: &[impl Serialize]) -> anyhow::Result<()> {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(records)?),
+        Format::Ndjson => {
+            for record in records {
+                print_json_line(record)?;
+            }
+        }
+        Format::Text => {}
+    }
+    Ok(())
+}
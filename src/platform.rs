@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 // Compile time detection of platform we're running on.
 pub const PLATFORM: Platform = if cfg!(target_os = "windows") {
     if cfg!(target_arch = "x86") {
@@ -21,7 +23,8 @@ pub const PLATFORM: Platform = if cfg!(target_os = "windows") {
     Platform::Unsupported
 };
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Platform {
     Windows32,
     Windows64,
@@ -32,7 +35,16 @@ pub enum Platform {
 }
 
 impl Platform {
-    pub fn to_package(&self) -> &'static str {
+    /// Every platform Godot publishes builds for.
+    pub const ALL: [Platform; 5] = [
+        Platform::Windows32,
+        Platform::Windows64,
+        Platform::MacOS,
+        Platform::Linux32,
+        Platform::Linux64,
+    ];
+
+    pub fn to_package(self) -> &'static str {
         match self {
             Platform::Windows32 => "win32.exe",
             Platform::Windows64 => "win64.exe",
@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+pub static PROJECT_GODOT_NAME: &str = "project.godot";
+
+/// The few settings fyg cares about from a project's project.godot file.
+#[derive(Debug, Default)]
+pub struct ProjectGodot {
+    pub name: Option<String>,
+    pub features: Vec<String>,
+}
+
+impl ProjectGodot {
+    pub fn load(project_godot_path: &Path) -> Result<ProjectGodot> {
+        let contents = fs::read_to_string(project_godot_path)
+            .with_context(|| format!("Could not read {}.", project_godot_path.display()))?;
+        Ok(Self::parse(&contents))
+    }
+
+    fn parse(contents: &str) -> ProjectGodot {
+        let mut project = ProjectGodot::default();
+        let mut section = "";
+        for line in contents.lines() {
+            let line = line.trim();
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name;
+                continue;
+            }
+            if section != "application" {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "config/name" => project.name = Some(unquote(value.trim()).to_string()),
+                "config/features" => {
+                    // Godot 4 uses PackedStringArray(...) and Godot 3 uses PoolStringArray(...).
+                    let value = value.trim();
+                    let Some(list) = value.split_once('(').and_then(|(_, rest)| rest.strip_suffix(')')) else {
+                        continue;
+                    };
+                    project.features = list.split(',')
+                        .map(|feature| unquote(feature.trim()).to_string())
+                        .filter(|feature| !feature.is_empty())
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        project
+    }
+
+    /// The engine version this project was last saved with, taken from its features. e.g. "4.3"
+    pub fn engine_version(&self) -> Option<&str> {
+        self.features.iter()
+            .find(|feature| {
+                feature.starts_with(|c: char| c.is_ascii_digit()) &&
+                    feature.chars().all(|c| c.is_ascii_digit() || c == '.')
+            })
+            .map(String::as_str)
+    }
+
+    /// Whether the project uses C# and so needs the Mono build of the engine.
+    pub fn uses_csharp(&self) -> bool {
+        self.features.iter().any(|feature| feature == "C#")
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// How many directories deep below the starting directory to look for a project.godot.
+const MAX_SEARCH_DEPTH: usize = 3;
+
+/// Directories that hold other people's code or build output rather than a Godot project.
+static SKIPPED_DIR_NAMES: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+];
+
+/// Find the project.godot closest to `dir`, searching `dir` first and then its subdirectories
+/// breadth first, up to `MAX_SEARCH_DEPTH` levels down. Hidden and vendor directories are skipped.
+pub fn find_project_godot(dir: &Path) -> Result<Option<PathBuf>> {
+    let mut queue = VecDeque::from([(dir.to_owned(), 0)]);
+    while let Some((dir, depth)) = queue.pop_front() {
+        let project_godot_path = dir.join(PROJECT_GODOT_NAME);
+        if project_godot_path.is_file() {
+            return Ok(Some(project_godot_path));
+        }
+        if depth == MAX_SEARCH_DEPTH {
+            continue;
+        }
+
+        let read_dir = fs::read_dir(&dir)
+            .with_context(|| format!("Could not read directory {}.", dir.display()))?;
+        let mut subdirs = Vec::new();
+        for entry in read_dir {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            if file_name.starts_with('.') || SKIPPED_DIR_NAMES.contains(&file_name.as_ref()) {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                subdirs.push(entry.path());
+            }
+        }
+        // Sort so the search order doesn't depend on the file system.
+        subdirs.sort();
+        queue.extend(subdirs.into_iter().map(|subdir| (subdir, depth + 1)));
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dirs::FygDirs,
+    engine::{self, Variant},
+    version::get_full_version,
+};
+
+/// Every project fyg knows about, either from opening it or importing it from an engine.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProjectRegistry {
+    #[serde(default, rename = "project")]
+    pub projects: Vec<KnownProject>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct KnownProject {
+    /// The project's directory, containing its fyg config or project.godot.
+    pub path: PathBuf,
+    /// The directory containing project.godot, if it's not `path`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub godot_dir: Option<PathBuf>,
+    /// The project's name from its project.godot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The engine version the project is pinned to.
+    pub version: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mono: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_opened: Option<DateTime<Utc>>,
+}
+
+impl KnownProject {
+    pub fn variant(&self) -> Variant {
+        Variant::from_mono(self.mono)
+    }
+
+    pub fn godot_dir(&self) -> &Path {
+        self.godot_dir.as_deref()
+            .unwrap_or(&self.path)
+    }
+
+    /// Whether the project's engine is installed, either vendored in the project like `edit` looks
+    /// for first, or shared with other projects.
+    pub fn is_engine_installed(&self) -> bool {
+        let full_version = get_full_version(&self.version);
+        let vendored_engines_dir = engine::vendored_engines_dir(&self.path);
+        engine::bin_path_in(&vendored_engines_dir, &full_version, self.variant()).is_file() ||
+            engine::bin_path(&full_version, self.variant()).is_file()
+    }
+}
+
+impl ProjectRegistry {
+    pub fn load() -> Result<ProjectRegistry> {
+        let registry_path = FygDirs::get().projects_registry();
+        if !registry_path.is_file() {
+            return Ok(Self::default());
+        }
+        let registry_str = fs::read_to_string(registry_path)
+            .with_context(|| format!("Could not read {}.", registry_path.display()))?;
+        toml::from_str(&registry_str)
+            .with_context(|| format!("Could not parse {}.", registry_path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let registry_path = FygDirs::get().projects_registry();
+        if let Some(parent) = registry_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(registry_path, toml::to_string(self)?)
+            .with_context(|| format!("Could not write {}.", registry_path.display()))
+    }
+
+    /// Find the project at `path`, whether that's its fyg config's or its project.godot's directory.
+    pub fn find(&self, path: &Path) -> Option<&KnownProject> {
+        self.projects.iter()
+            .find(|project| project.path == path || project.godot_dir() == path)
+    }
+
+    /// Add a project, replacing any existing entry for the same path.
+    pub fn insert(&mut self, project: KnownProject) {
+        self.forget(&project.path);
+        self.projects.push(project);
+    }
+
+    /// Remove the project at `path`, returning whether it was known.
+    pub fn forget(&mut self, path: &Path) -> bool {
+        let len = self.projects.len();
+        self.projects.retain(|project| project.path != path);
+        self.projects.len() != len
+    }
+}
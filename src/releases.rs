@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use octocrab::models::repos::Release;
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    checksum::parse_sums,
+    config::UserConfig,
+    dirs::FygDirs,
+    logging,
+};
+
+pub static SHA512_SUMS_NAME: &str = "SHA512-SUMS.txt";
+
+/// A release's GitHub metadata, as kept in its cache directory by `fyg bundle`.
+pub static RELEASE_METADATA_NAME: &str = "release.json";
+
+/// File in the cache listing the tags of every release seen by `list_releases`, so they can be
+/// completed offline.
+static RELEASE_INDEX_NAME: &str = "release-index.json";
+
+/// How often to redraw download progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Split a release source like "godotengine/godot" into its owner and repository.
+pub fn split_source(source: &str) -> Result<(&str, &str)> {
+    source.split_once('/')
+        .ok_or_else(|| anyhow!("Invalid release source \"{}\". Expected \"owner/repo\".", source))
+}
+
+/// Look up the GitHub release for a full version. e.g. "4.3-stable". Falls back to metadata in the
+/// cache, so imported bundles work offline.
+#[instrument]
+pub async fn get_release(full_version: &str) -> Option<Release> {
+    let octocrab = octocrab::instance();
+    for source in UserConfig::get().sources() {
+        let Ok((owner, repo)) = split_source(source) else {
+            continue;
+        };
+        let maybe_release = octocrab.repos(owner, repo)
+            .releases()
+            .get_by_tag(full_version)
+            .await;
+        match maybe_release {
+            Ok(release) => {
+                info!(source, "Found release");
+                return Some(release);
+            }
+            Err(err) => debug!(source, error = %err, "Release not found in source"),
+        }
+    }
+    let release = get_cached_release(full_version);
+    if release.is_some() {
+        info!("Using cached release metadata");
+    }
+    release
+}
+
+/// Read a release's metadata from its cache directory, if it's there.
+pub fn get_cached_release(full_version: &str) -> Option<Release> {
+    let metadata_path = FygDirs::get().engines_cache()
+        .join(full_version)
+        .join(RELEASE_METADATA_NAME);
+    let metadata = fs::read_to_string(metadata_path).ok()?;
+    serde_json::from_str(&metadata).ok()
+}
+
+/// Every release in the user's sources. Releases with the same tag in more than one source are only
+/// listed from the first.
+pub async fn list_releases() -> Result<Vec<Release>> {
+    list_releases_by_page(|_| Ok(())).await
+}
+
+/// Like `list_releases`, but also hand each page's new releases to `on_page` as soon as it's
+/// fetched, e.g. to print them before the rest come in.
+#[instrument(skip(on_page))]
+pub async fn list_releases_by_page(mut on_page: impl FnMut(&[Release]) -> Result<()>) -> Result<Vec<Release>> {
+    let octocrab = octocrab::instance();
+    let mut releases: Vec<Release> = Vec::new();
+    for source in UserConfig::get().sources() {
+        let (owner, repo) = split_source(source)?;
+        let mut page = octocrab.repos(owner, repo)
+            .releases()
+            .list()
+            .per_page(100)
+            .send()
+            .await?;
+        loop {
+            let page_start = releases.len();
+            for release in page.items.drain(..) {
+                if !releases.iter().any(|known| known.tag_name == release.tag_name) {
+                    releases.push(release);
+                }
+            }
+            on_page(&releases[page_start..])?;
+
+            // Try to get the next page, if any.
+            page = match octocrab
+                .get_page::<Release>(&page.next)
+                .await?
+            {
+                Some(next_page) => next_page,
+                None => break,
+            }
+        }
+    }
+
+    info!(count = releases.len(), "Listed releases");
+
+    // Not being able to update the index only makes completions stale.
+    let tags: Vec<&str> = releases.iter()
+        .map(|release| release.tag_name.as_str())
+        .collect();
+    if let Err(err) = save_release_index(&tags) {
+        warn!("Could not save the release index. {:#}", err);
+    }
+
+    Ok(releases)
+}
+
+fn save_release_index(tags: &[&str]) -> Result<()> {
+    let (cache_dir, _) = FygDirs::get().cache();
+    fs::create_dir_all(cache_dir)?;
+    fs::write(cache_dir.join(RELEASE_INDEX_NAME), serde_json::to_string(tags)?)?;
+    Ok(())
+}
+
+/// Tags of every release from the last time they were all listed, or nothing if they never were.
+pub fn get_indexed_tags() -> Vec<String> {
+    let (cache_dir, _) = FygDirs::get().cache();
+    fs::read_to_string(cache_dir.join(RELEASE_INDEX_NAME))
+        .ok()
+        .and_then(|index| serde_json::from_str(&index).ok())
+        .unwrap_or_default()
+}
+
+/// Download and parse the SHA512 sums published with a release, keyed by asset name. Returns an
+/// empty map for releases that don't publish sums.
+#[instrument(skip_all, fields(tag = %release.tag_name))]
+pub async fn get_sha512_sums(release: &Release) -> Result<HashMap<String, String>> {
+    let Some(sums_asset) = release.assets.iter()
+        .find(|asset| asset.name == SHA512_SUMS_NAME)
+    else {
+        return Ok(HashMap::new());
+    };
+
+    let sums = download(sums_asset.browser_download_url.as_str()).await?;
+    Ok(parse_sums(&String::from_utf8_lossy(&sums)))
+}
+
+/// URLs to try downloading a release asset from on the user's mirrors, in order.
+pub fn mirror_urls(full_version: &str, asset_name: &str) -> Vec<String> {
+    UserConfig::get().mirrors.iter()
+        .map(|mirror| mirror.replace("{tag}", full_version).replace("{asset}", asset_name))
+        .collect()
+}
+
+/// Try downloading a release asset from each of the user's mirrors.
+#[instrument]
+pub async fn download_from_mirrors(full_version: &str, asset_name: &str) -> Option<(String, bytes::Bytes)> {
+    for url in mirror_urls(full_version, asset_name) {
+        match download_with_progress(&url).await {
+            Ok(content) => return Some((url, content)),
+            Err(err) => warn!("Could not download from mirror {}: {}", url, err),
+        }
+    }
+    None
+}
+
+/// Get the SHA512 sums for a release from the user's mirrors, if any of them have it.
+#[instrument]
+pub async fn get_mirror_sha512_sums(full_version: &str) -> HashMap<String, String> {
+    for url in mirror_urls(full_version, SHA512_SUMS_NAME) {
+        if let Ok(sums) = download(&url).await {
+            return parse_sums(&String::from_utf8_lossy(&sums));
+        }
+    }
+    HashMap::new()
+}
+
+#[instrument]
+pub async fn download(url: &str) -> Result<bytes::Bytes> {
+    let start = Instant::now();
+    let response = reqwest::get(url)
+        .await?
+        .error_for_status()?;
+    let content = response.bytes().await?;
+    debug!(size = content.len(), elapsed_ms = start.elapsed().as_millis() as u64, "Downloaded");
+    Ok(content)
+}
+
+/// Download a file, showing how much has been downloaded on stderr as it goes if that's a terminal.
+#[instrument]
+pub async fn download_with_progress(url: &str) -> Result<bytes::Bytes> {
+    let start = Instant::now();
+    let mut response = reqwest::get(url)
+        .await?
+        .error_for_status()?;
+    let total_size = response.content_length();
+    debug!(?total_size, "Started download");
+    if logging::is_quiet() || !io::stderr().is_terminal() {
+        let content = response.bytes().await?;
+        info!(size = content.len(), elapsed_ms = start.elapsed().as_millis() as u64, "Downloaded");
+        return Ok(content);
+    }
+
+    let mut content = Vec::with_capacity(total_size.unwrap_or_default() as usize);
+    let mut last_shown: Option<Instant> = None;
+    while let Some(chunk) = response.chunk().await? {
+        content.extend_from_slice(&chunk);
+        if last_shown.map_or(true, |last_shown| last_shown.elapsed() >= PROGRESS_INTERVAL) {
+            show_progress(content.len() as u64, total_size);
+            last_shown = Some(Instant::now());
+        }
+    }
+    show_progress(content.len() as u64, total_size);
+    eprintln!();
+    info!(size = content.len(), elapsed_ms = start.elapsed().as_millis() as u64, "Downloaded");
+
+    Ok(content.into())
+}
+
+fn show_progress(downloaded: u64, total_size: Option<u64>) {
+    let formatted_downloaded = humansize::format_size(downloaded, humansize::DECIMAL);
+    let progress = match total_size {
+        Some(total_size) if total_size > 0 => format!(
+            "Downloaded {} of {} ({}%)",
+            formatted_downloaded,
+            humansize::format_size(total_size, humansize::DECIMAL),
+            downloaded * 100 / total_size,
+        ),
+        _ => format!("Downloaded {}", formatted_downloaded),
+    };
+    // Pad over whatever was shown before, since it may have been longer.
+    eprint!("\r{:<50}", progress);
+}
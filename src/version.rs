@@ -1,3 +1,5 @@
+use std::fmt;
+
 pub fn get_full_version(version: &str) -> String {
     // TODO: Use a more thorough heuristic.
     if version.contains('-') {
@@ -6,3 +8,140 @@ pub fn get_full_version(version: &str) -> String {
         format!("{}-stable", version)
     }
 }
+
+/// A parsed Godot version like "4.2.1-stable" or "4.3-rc2", ordered by release.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub stage: Stage,
+}
+
+/// How far along a version's release is. Later variants are newer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    Dev(u32),
+    Alpha(u32),
+    Beta(u32),
+    Rc(u32),
+    Stable,
+}
+
+impl Version {
+    /// Parse a version or full version. Versions without a stage are stable.
+    pub fn parse(version: &str) -> Option<Version> {
+        let (number, stage) = version.split_once('-')
+            .unwrap_or((version, "stable"));
+
+        let mut parts = number.split('.')
+            .map(|part| part.parse::<u32>().ok());
+        let major = parts.next()??;
+        let minor = parts.next().unwrap_or(Some(0))?;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let stage = if stage == "stable" {
+            Stage::Stable
+        } else {
+            let split = stage.find(|c: char| c.is_ascii_digit())
+                .unwrap_or(stage.len());
+            let (name, number) = stage.split_at(split);
+            let number = if number.is_empty() {
+                0
+            } else {
+                number.parse().ok()?
+            };
+            match name {
+                "dev" => Stage::Dev(number),
+                "alpha" => Stage::Alpha(number),
+                "beta" => Stage::Beta(number),
+                "rc" => Stage::Rc(number),
+                _ => return None,
+            }
+        };
+
+        Some(Version {
+            major,
+            minor,
+            patch,
+            stage,
+        })
+    }
+
+    /// The minor series this version belongs to. e.g. (4, 2) for "4.2.1"
+    pub fn minor_series(&self) -> (u32, u32) {
+        (self.major, self.minor)
+    }
+}
+
+impl fmt::Display for Version {
+    /// Formats like Godot's release tags, without a stable suffix. e.g. "4.2.1" or "4.3-rc2"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.patch != 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        match self.stage {
+            Stage::Dev(number) => write!(f, "-dev{}", number),
+            Stage::Alpha(number) => write!(f, "-alpha{}", number),
+            Stage::Beta(number) => write!(f, "-beta{}", number),
+            Stage::Rc(number) => write!(f, "-rc{}", number),
+            Stage::Stable => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(version: &str) -> Version {
+        Version::parse(version).unwrap_or_else(|| panic!("{} should parse", version))
+    }
+
+    #[test]
+    fn stages_are_ordered_by_release() {
+        let versions = ["4.3-dev6", "4.3-alpha1", "4.3-beta3", "4.3-rc1", "4.3-rc2", "4.3-stable"];
+        for pair in versions.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} should come before {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn numbers_are_compared_as_numbers() {
+        assert!(parse("4.2.2") < parse("4.10"));
+        assert!(parse("3.6") < parse("4.0-dev1"));
+        assert!(parse("4.3-beta9") < parse("4.3-beta10"));
+    }
+
+    #[test]
+    fn missing_parts_are_zero() {
+        assert_eq!(parse("4"), parse("4.0"));
+        assert_eq!(parse("4.0"), parse("4.0.0-stable"));
+        assert_ne!(parse("4"), parse("4.0.1"));
+        assert_eq!(parse("4.3-rc").stage, Stage::Rc(0));
+    }
+
+    #[test]
+    fn invalid_versions_are_rejected() {
+        for version in ["", "four", "4.x", "4.3.1.2", "4.3-preview1", "4.3-rcX", "-stable"] {
+            assert_eq!(Version::parse(version), None, "{:?} should not parse", version);
+        }
+    }
+
+    #[test]
+    fn display_matches_release_tags() {
+        assert_eq!(parse("4.2.1-stable").to_string(), "4.2.1");
+        assert_eq!(parse("4.0").to_string(), "4.0");
+        assert_eq!(parse("4.3-rc2").to_string(), "4.3-rc2");
+    }
+
+    #[test]
+    fn full_version_adds_stable() {
+        assert_eq!(get_full_version("4.3"), "4.3-stable");
+        assert_eq!(get_full_version("4.3-rc2"), "4.3-rc2");
+    }
+}